@@ -1,18 +1,227 @@
-use aws_config::BehaviorVersion;
+use aws_config::profile::profile_file::{ProfileFileKind, ProfileFiles};
+use aws_config::retry::RetryConfig;
+use aws_config::{BehaviorVersion, SdkConfig};
 use aws_sdk_secretsmanager::config::Region;
 use aws_sdk_secretsmanager::Client;
 
-pub async fn build_client(region: Option<&str>, profile: Option<&str>) -> Client {
-    let mut config_builder = aws_config::defaults(BehaviorVersion::latest());
+use crate::errors::SmError;
 
-    if let Some(r) = region {
-        config_builder = config_builder.region(Region::new(r.to_string()));
+/// Default number of retries for throttled or transient API failures.
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// Default cap on in-flight GetSecretValue calls when fetching many secrets.
+pub const DEFAULT_MAX_CONCURRENCY: usize = 8;
+
+/// Commercial regions where Secrets Manager is available, queried by
+/// `list --all-regions`. Opt-in regions that are not enabled fail and are skipped.
+pub const SECRETS_MANAGER_REGIONS: &[&str] = &[
+    "us-east-1",
+    "us-east-2",
+    "us-west-1",
+    "us-west-2",
+    "af-south-1",
+    "ap-east-1",
+    "ap-south-1",
+    "ap-south-2",
+    "ap-southeast-1",
+    "ap-southeast-2",
+    "ap-southeast-3",
+    "ap-southeast-4",
+    "ap-northeast-1",
+    "ap-northeast-2",
+    "ap-northeast-3",
+    "ca-central-1",
+    "ca-west-1",
+    "eu-central-1",
+    "eu-central-2",
+    "eu-west-1",
+    "eu-west-2",
+    "eu-west-3",
+    "eu-south-1",
+    "eu-south-2",
+    "eu-north-1",
+    "il-central-1",
+    "me-south-1",
+    "me-central-1",
+    "sa-east-1",
+];
+
+/// Region of a Secrets Manager ARN (`arn:aws:secretsmanager:<region>:<account>:secret:<name>`),
+/// or `None` for a plain secret name.
+pub fn region_from_arn(secret_id: &str) -> Option<&str> {
+    let mut parts = secret_id.splitn(6, ':');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some("arn"), Some(_partition), Some("secretsmanager"), Some(region)) if !region.is_empty() => Some(region),
+        _ => None,
+    }
+}
+
+/// The region a client needs to reach every secret in `names`: an ARN's own
+/// region, or `default_region` for plain names. One client only reaches one
+/// region, so secrets spread over several regions are an error.
+pub fn common_region<'a>(
+    names: &'a [String],
+    default_region: Option<&'a str>,
+) -> Result<Option<&'a str>, SmError> {
+    let mut by_region: std::collections::BTreeMap<&str, Vec<&str>> = std::collections::BTreeMap::new();
+    for name in names {
+        if let Some(region) = region_from_arn(name).or(default_region) {
+            by_region.entry(region).or_default().push(name);
+        }
+    }
+    if by_region.len() > 1 {
+        let groups: Vec<String> = by_region
+            .iter()
+            .map(|(region, names)| format!("{}: {}", region, names.join(", ")))
+            .collect();
+        return Err(SmError::FormatError(format!(
+            "secrets are in different regions ({}); fetch each region separately or pass --region",
+            groups.join("; ")
+        )));
+    }
+    Ok(by_region.into_keys().next().or(default_region))
+}
+
+/// Friendly name of a secret given by ARN: `arn:...:secret:prod/db-AbCdEf` gives
+/// `prod/db`. The `-` and six characters Secrets Manager appends are dropped;
+/// plain names are returned unchanged.
+pub fn secret_basename(secret_id: &str) -> &str {
+    if region_from_arn(secret_id).is_none() {
+        return secret_id;
+    }
+    let name = match secret_id.splitn(7, ':').nth(6) {
+        Some(name) if !name.is_empty() => name,
+        _ => return secret_id,
+    };
+    match name.rsplit_once('-') {
+        Some((base, suffix))
+            if !base.is_empty() && suffix.len() == 6 && suffix.chars().all(|c| c.is_ascii_alphanumeric()) =>
+        {
+            base
+        }
+        _ => name,
+    }
+}
+
+/// Non-default locations of the shared AWS config and credentials files,
+/// equivalent to `AWS_CONFIG_FILE` and `AWS_SHARED_CREDENTIALS_FILE`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProfileFilePaths<'a> {
+    pub config: Option<&'a str>,
+    pub credentials: Option<&'a str>,
+}
+
+impl ProfileFilePaths<'_> {
+    /// Profile files for the loader; `None` keeps the SDK defaults entirely.
+    fn to_profile_files(self) -> Option<ProfileFiles> {
+        if self.config.is_none() && self.credentials.is_none() {
+            return None;
+        }
+        let mut builder = ProfileFiles::builder();
+        builder = match self.config {
+            Some(path) => builder.with_file(ProfileFileKind::Config, path),
+            None => builder.include_default_config_file(true),
+        };
+        builder = match self.credentials {
+            Some(path) => builder.with_file(ProfileFileKind::Credentials, path),
+            None => builder.include_default_credentials_file(true),
+        };
+        Some(builder.build())
+    }
+}
+
+/// Where the region used for API calls came from, reported by `--verbose`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RegionSource {
+    /// `--region`, or the region of a secret given by ARN
+    Flag,
+    EnvAwsRegion,
+    EnvAwsDefaultRegion,
+    /// The AWS profile, or another SDK default source
+    Profile,
+    /// `region` in ~/.sm2env
+    Config,
+}
+
+impl std::fmt::Display for RegionSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            RegionSource::Flag => "--region",
+            RegionSource::EnvAwsRegion => "AWS_REGION",
+            RegionSource::EnvAwsDefaultRegion => "AWS_DEFAULT_REGION",
+            RegionSource::Profile => "AWS profile",
+            RegionSource::Config => "~/.sm2env",
+        })
+    }
+}
+
+/// The region chosen before the SDK loads its config: `flag` first, then the
+/// non-empty `AWS_REGION` and `AWS_DEFAULT_REGION` variables read via `env`.
+/// `None` leaves the choice to the profile and then ~/.sm2env.
+pub fn explicit_region(
+    flag: Option<&str>,
+    env: impl Fn(&str) -> Option<String>,
+) -> Option<(String, RegionSource)> {
+    if let Some(region) = flag {
+        return Some((region.to_string(), RegionSource::Flag));
+    }
+    [
+        ("AWS_REGION", RegionSource::EnvAwsRegion),
+        ("AWS_DEFAULT_REGION", RegionSource::EnvAwsDefaultRegion),
+    ]
+    .into_iter()
+    .find_map(|(var, source)| env(var).filter(|v| !v.is_empty()).map(|v| (v, source)))
+}
+
+/// Build a Secrets Manager client. Throttling and transient errors are retried
+/// by the SDK's standard retry strategy (jittered exponential backoff) up to
+/// `max_retries` times.
+pub async fn build_client(
+    region: Option<&str>,
+    fallback_region: Option<&str>,
+    profile: Option<&str>,
+    max_retries: u32,
+    profile_files: ProfileFilePaths<'_>,
+) -> (Client, Option<(String, RegionSource)>) {
+    let (config, resolved) = load_sdk_config(region, fallback_region, profile, max_retries, profile_files).await;
+    (Client::new(&config), resolved)
+}
+
+/// Load the shared SDK config behind [`build_client`], for clients of other
+/// services (e.g. SSM for `--to-ssm`), with the same region and retry handling.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn load_sdk_config(
+    region: Option<&str>,
+    fallback_region: Option<&str>,
+    profile: Option<&str>,
+    max_retries: u32,
+    profile_files: ProfileFilePaths<'_>,
+) -> (SdkConfig, Option<(String, RegionSource)>) {
+    let mut config_builder = aws_config::defaults(BehaviorVersion::latest())
+        .retry_config(RetryConfig::standard().with_max_attempts(max_retries + 1));
+
+    let explicit = explicit_region(region, |var| std::env::var(var).ok());
+    if let Some((r, _)) = &explicit {
+        config_builder = config_builder.region(Region::new(r.clone()));
     }
 
     if let Some(p) = profile {
         config_builder = config_builder.profile_name(p);
     }
 
-    let config = config_builder.load().await;
-    Client::new(&config)
+    if let Some(files) = profile_files.to_profile_files() {
+        config_builder = config_builder.profile_files(files);
+    }
+
+    let mut config = config_builder.load().await;
+    let resolved = match (explicit, config.region(), fallback_region) {
+        (Some(explicit), _, _) => Some(explicit),
+        (None, Some(r), _) => Some((r.to_string(), RegionSource::Profile)),
+        (None, None, Some(r)) => {
+            config = config.into_builder().region(Region::new(r.to_string())).build();
+            Some((r.to_string(), RegionSource::Config))
+        }
+        (None, None, None) => None,
+    };
+    (config, resolved)
 }
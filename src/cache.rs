@@ -0,0 +1,109 @@
+use crate::errors::SmError;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Raw secret content as returned by the API; binary secrets are kept base64-encoded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SecretValue {
+    pub secret_string: Option<String>,
+    pub secret_binary: Option<String>,
+    /// Version the content belongs to; absent in entries cached by older releases
+    #[serde(default)]
+    pub version_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    fetched_at: u64,
+    value: SecretValue,
+}
+
+/// On-disk cache of fetched secrets keyed by secret name and version stage,
+/// within a [`scoped`](Cache::scoped) region and profile.
+/// Entries are written with `0600` permissions since they hold secret values.
+pub struct Cache {
+    dir: PathBuf,
+    ttl: Duration,
+    refresh: bool,
+    scope: String,
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf, ttl: Duration) -> Self {
+        Cache { dir, ttl, refresh: false, scope: String::new() }
+    }
+
+    /// Treat every lookup as a miss while still storing fetched values, so stale
+    /// entries are replaced (e.g. right after a rotation).
+    pub fn refreshing(self) -> Self {
+        Cache { refresh: true, ..self }
+    }
+
+    /// Keep entries apart per AWS region and profile, so the same secret name in
+    /// another account or region is never served from this one's entry.
+    pub fn scoped(self, region: Option<&str>, profile: Option<&str>) -> Self {
+        let scope = format!("{}\0{}", region.unwrap_or_default(), profile.unwrap_or_default());
+        Cache { scope, ..self }
+    }
+
+    /// Cache rooted at `$XDG_CACHE_HOME/sm2env`, falling back to `~/.cache/sm2env`.
+    pub fn in_default_dir(ttl: Duration) -> Self {
+        Cache::new(default_cache_dir(), ttl)
+    }
+
+    /// Return the cached value if it was stored less than `ttl` before `now`.
+    /// Missing, unreadable, or expired entries are treated as a miss.
+    pub fn get(&self, secret_name: &str, version_stage: &str, now: SystemTime) -> Option<SecretValue> {
+        if self.refresh {
+            return None;
+        }
+        let content = std::fs::read_to_string(self.entry_path(secret_name, version_stage)).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&content).ok()?;
+        let age = epoch_secs(now).checked_sub(entry.fetched_at)?;
+        if age < self.ttl.as_secs() {
+            Some(entry.value)
+        } else {
+            None
+        }
+    }
+
+    pub fn put(
+        &self,
+        secret_name: &str,
+        version_stage: &str,
+        value: &SecretValue,
+        now: SystemTime,
+    ) -> Result<(), SmError> {
+        crate::output::create_private_dir(&self.dir)?;
+        let entry = CacheEntry {
+            fetched_at: epoch_secs(now),
+            value: value.clone(),
+        };
+        let content = serde_json::to_string(&entry)?;
+        crate::output::write_secure(&self.entry_path(secret_name, version_stage), &content)
+    }
+
+    /// Hex-encode the key so any secret name or ARN maps to a safe, unique file name.
+    fn entry_path(&self, secret_name: &str, version_stage: &str) -> PathBuf {
+        let key: String = format!("{}\0{}\0{}", self.scope, secret_name, version_stage)
+            .bytes()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        self.dir.join(format!("{}.json", key))
+    }
+}
+
+fn epoch_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn default_cache_dir() -> PathBuf {
+    if let Ok(xdg) = std::env::var("XDG_CACHE_HOME") {
+        return PathBuf::from(xdg).join("sm2env");
+    }
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".cache").join("sm2env")
+}
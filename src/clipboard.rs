@@ -0,0 +1,19 @@
+//! System clipboard access for `get --clipboard`, built with the `clipboard` feature.
+
+use sm2env::errors::SmError;
+
+#[cfg(feature = "clipboard")]
+pub fn copy(text: &str) -> Result<(), SmError> {
+    let mut clipboard =
+        arboard::Clipboard::new().map_err(|e| SmError::ClipboardError(e.to_string()))?;
+    clipboard
+        .set_text(text.to_string())
+        .map_err(|e| SmError::ClipboardError(e.to_string()))
+}
+
+#[cfg(not(feature = "clipboard"))]
+pub fn copy(_text: &str) -> Result<(), SmError> {
+    Err(SmError::ClipboardError(
+        "sm2env was built without clipboard support; rebuild with `--features clipboard`".to_string(),
+    ))
+}
@@ -9,6 +9,8 @@ pub struct Config {
     /// Default output format (env, json, yaml, csv, stdout)
     #[allow(dead_code)]
     pub format: Option<String>,
+    /// Cache fetched secrets for this many seconds (disabled when unset)
+    pub cache_ttl: Option<u64>,
 }
 
 pub fn load_config() -> Result<Config, SmError> {
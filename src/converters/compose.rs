@@ -0,0 +1,22 @@
+use crate::errors::SmError;
+use serde_json::{Map, Value};
+
+/// Render a Docker Compose `environment:` fragment, either in list form
+/// (`- KEY=value`) or, with `as_map`, in mapping form (`KEY: "value"`).
+/// Values are stringified and `$` is doubled so Compose does not interpolate it.
+pub fn convert(data: &Map<String, Value>, as_map: bool) -> Result<String, SmError> {
+    let pairs = data.iter().map(|(key, value)| {
+        let value_str = value
+            .as_str()
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| value.to_string());
+        (key.clone(), value_str.replace('$', "$$"))
+    });
+
+    let fragment = if as_map {
+        Value::Object(pairs.map(|(k, v)| (k, Value::String(v))).collect())
+    } else {
+        Value::Array(pairs.map(|(k, v)| Value::String(format!("{}={}", k, v))).collect())
+    };
+    Ok(serde_yml::to_string(&fragment)?)
+}
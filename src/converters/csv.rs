@@ -1,9 +1,16 @@
+use super::ConvertOptions;
 use crate::errors::SmError;
 use serde_json::{Map, Value};
 
-pub fn convert(data: &Map<String, Value>) -> Result<String, SmError> {
-    let mut writer = csv::Writer::from_writer(vec![]);
-    writer.write_record(["key", "value"])?;
+/// Render `key,value` rows with RFC 4180 quoting. The delimiter and the header
+/// row are configurable; values containing the delimiter are always quoted.
+pub fn convert(data: &Map<String, Value>, opts: &ConvertOptions) -> Result<String, SmError> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(opts.csv_delimiter.unwrap_or(b','))
+        .from_writer(vec![]);
+    if !opts.csv_no_header {
+        writer.write_record(["key", "value"])?;
+    }
     for (key, value) in data {
         let value_str = value
             .as_str()
@@ -15,3 +22,12 @@ pub fn convert(data: &Map<String, Value>) -> Result<String, SmError> {
     let csv_content = String::from_utf8(inner)?;
     Ok(csv_content)
 }
+
+/// Parse a `--delimiter` value: one ASCII character, or `\t` for a tab.
+pub fn parse_delimiter(s: &str) -> Result<u8, String> {
+    match s {
+        "\\t" => Ok(b'\t'),
+        _ if s.len() == 1 && s.is_ascii() => Ok(s.as_bytes()[0]),
+        _ => Err(format!("delimiter must be a single ASCII character, got '{}'", s)),
+    }
+}
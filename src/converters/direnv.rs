@@ -0,0 +1,16 @@
+use super::{shell, ConvertOptions};
+use serde_json::{Map, Value};
+
+/// First line of every generated `.envrc`.
+pub const HEADER: &str = "# managed by sm2env";
+
+/// Render a direnv `.envrc`: a header comment followed by one
+/// `export KEY=value` line per key, with values shell-quoted.
+pub fn convert(data: &Map<String, Value>, opts: &ConvertOptions) -> String {
+    let mut content = format!("{}\n", HEADER);
+    for (key, value) in super::cased_entries(data, opts) {
+        let value_str = super::env_value_string(value, opts);
+        content.push_str(&format!("export {}={}\n", opts.key_case.apply(key), shell::quote(&value_str)));
+    }
+    content
+}
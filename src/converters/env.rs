@@ -1,13 +1,173 @@
+use super::ConvertOptions;
+use crate::detect::EnvLine;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use serde_json::{Map, Value};
 
-pub fn convert(data: &Map<String, Value>) -> String {
+/// Everything except RFC 3986 unreserved characters (`A-Z a-z 0-9 - . _ ~`).
+const URL_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+/// Render `KEY=VALUE` lines. This is the single env builder for both `--output env`
+/// files and `--output stdout`, so the two always agree byte for byte.
+/// Values with embedded newlines are escaped, so every entry stays on one line;
+/// with `env_url_encode` every value is percent-encoded instead.
+/// Unlike direnv output, `env_export_prefix` adds `export ` but leaves values unquoted.
+/// With `example`, values are replaced by [`example_placeholder`]s. When `key_case`
+/// maps several keys to one name, only the first is written.
+pub fn convert(data: &Map<String, Value>, opts: &ConvertOptions) -> String {
+    let width = key_width(data, opts);
+    super::cased_entries(data, opts)
+        .map(|(key, value)| line(key, value, width, opts))
+        .collect()
+}
+
+/// [`convert`], laid out like the env text the data was parsed from: comment
+/// and blank lines stay where they were and each key keeps its original line.
+/// Keys the layout does not name (renamed, or added by `--append`) follow at the end.
+/// Keys that collide once `key_case` is applied are written once, as with [`convert`].
+pub fn convert_with_layout(data: &Map<String, Value>, layout: &[EnvLine], opts: &ConvertOptions) -> String {
+    let newline = opts.line_ending.as_str();
+    let width = key_width(data, opts);
+    // Keys dropped by a --key-case collision count as already written
+    let mut written: std::collections::HashSet<&str> =
+        opts.key_case.collisions(data).into_iter().map(|(key, _)| key).collect();
     let mut content = String::new();
+    for entry in layout {
+        match entry {
+            EnvLine::Verbatim(text) => {
+                content.push_str(text);
+                content.push_str(newline);
+            }
+            EnvLine::Key(key) => {
+                if let Some((key, value)) = data.get_key_value(key).filter(|(k, _)| written.insert(k.as_str())) {
+                    content.push_str(&line(key, value, width, opts));
+                }
+            }
+        }
+    }
     for (key, value) in data {
-        let value_str = value
-            .as_str()
-            .map(|s| s.to_string())
-            .unwrap_or_else(|| value.to_string());
-        content.push_str(&format!("{}={}\n", key, value_str.trim_matches('"')));
+        if !written.contains(key.as_str()) {
+            content.push_str(&line(key, value, width, opts));
+        }
     }
     content
 }
+
+/// Width keys are padded to with `env_align`, else 0.
+fn key_width(data: &Map<String, Value>, opts: &ConvertOptions) -> usize {
+    if opts.env_align {
+        data.keys().map(|k| k.chars().count()).max().unwrap_or(0)
+    } else {
+        0
+    }
+}
+
+/// One `KEY=value` line, including the line ending.
+fn line(key: &str, value: &Value, width: usize, opts: &ConvertOptions) -> String {
+    let value_str = if opts.example {
+        example_placeholder(key)
+    } else {
+        let value_str = super::env_value_string(value, opts);
+        let value_str = value_str.trim_matches('"');
+        let value_str = if opts.env_url_encode {
+            utf8_percent_encode(value_str, URL_ENCODE_SET).to_string().into()
+        } else {
+            escape_multiline(value_str)
+        };
+        if opts.env_escape_dollar {
+            value_str.replace('$', "$$")
+        } else {
+            value_str.to_string()
+        }
+    };
+    let key = opts.key_case.apply(key);
+    let export = if opts.env_export_prefix { "export " } else { "" };
+    let newline = opts.line_ending.as_str();
+    if opts.env_align {
+        format!("{}{:<width$} = {}{}", export, key, value_str, newline)
+    } else {
+        format!("{}{}={}{}", export, key, value_str, newline)
+    }
+}
+
+/// Placeholder for `--example` output, derived from the key: `DATABASE_URL` -> `<database_url>`.
+pub fn example_placeholder(key: &str) -> String {
+    format!("<{}>", key.to_lowercase())
+}
+
+/// Keep a value with embedded newlines on one line, dotenv-style: wrap it in
+/// double quotes with `\n`, `\r`, `\"`, and `\\` escaped. Other values are unchanged.
+fn escape_multiline(value: &str) -> std::borrow::Cow<'_, str> {
+    if !value.contains(['\n', '\r']) {
+        return value.into();
+    }
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r");
+    format!("\"{}\"", escaped).into()
+}
+
+/// Check a key against the POSIX environment variable name rule `[A-Za-z_][A-Za-z0-9_]*`.
+pub fn is_valid_env_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Turn `key` into a shell-safe name: ASCII letters are uppercased, any other
+/// character becomes `_`, and a leading digit gets a `_` prefix, so `api-key`
+/// becomes `API_KEY`. `None` when the key has no letters or digits to keep.
+pub fn normalize_env_key(key: &str) -> Option<String> {
+    if !key.chars().any(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let name: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        Some(format!("_{}", name))
+    } else {
+        Some(name)
+    }
+}
+
+/// What [`normalize_env_keys`] did to the keys it had to change.
+#[derive(Debug, Default, PartialEq)]
+pub struct KeyReport {
+    /// `(original, normalized)` for every key whose name changed
+    pub renamed: Vec<(String, String)>,
+    /// Keys left out: nothing to keep, or normalized onto a name already taken
+    pub dropped: Vec<String>,
+}
+
+/// Apply [`normalize_env_key`] to every key. When two keys normalize to the same
+/// name the first one wins and the later one is dropped.
+pub fn normalize_env_keys(data: Map<String, Value>) -> (Map<String, Value>, KeyReport) {
+    let mut normalized = Map::new();
+    let mut report = KeyReport::default();
+    for (key, value) in data {
+        match normalize_env_key(&key) {
+            Some(name) if !normalized.contains_key(&name) => {
+                if name != key {
+                    report.renamed.push((key, name.clone()));
+                }
+                normalized.insert(name, value);
+            }
+            _ => report.dropped.push(key),
+        }
+    }
+    (normalized, report)
+}
+
+/// Keys that are not valid environment variable names, in map order.
+pub fn invalid_env_keys(data: &Map<String, Value>) -> Vec<&str> {
+    data.keys()
+        .map(|k| k.as_str())
+        .filter(|k| !is_valid_env_key(k))
+        .collect()
+}
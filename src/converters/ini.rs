@@ -0,0 +1,57 @@
+use super::LineEnding;
+use serde_json::{Map, Value};
+
+/// Section holding top-level scalar values.
+pub const DEFAULT_SECTION: &str = "DEFAULT";
+
+/// Render INI: top-level scalars go under `[DEFAULT]` and each top-level object
+/// becomes its own `[section]` with its children as `key=value` lines.
+pub fn convert(data: &Map<String, Value>, line_ending: LineEnding) -> String {
+    let newline = line_ending.as_str();
+    let mut defaults = Vec::new();
+    let mut sections = Vec::new();
+    for (key, value) in data {
+        match value {
+            Value::Object(children) => sections.push((key, children)),
+            _ => defaults.push((key, value)),
+        }
+    }
+
+    let mut content = String::new();
+    if !defaults.is_empty() {
+        content.push_str(&format!("[{}]{}", DEFAULT_SECTION, newline));
+        for (key, value) in defaults {
+            content.push_str(&format!("{}={}{}", key, escape_ini(value), newline));
+        }
+    }
+    for (name, children) in sections {
+        if !content.is_empty() {
+            content.push_str(newline);
+        }
+        content.push_str(&format!("[{}]{}", name, newline));
+        for (key, value) in children {
+            content.push_str(&format!("{}={}{}", key, escape_ini(value), newline));
+        }
+    }
+    content
+}
+
+/// Format a value for an INI line. Values with comment characters, quotes,
+/// newlines, or surrounding whitespace are double-quoted with backslash escapes.
+/// Nested objects and arrays are written as JSON.
+pub fn escape_ini(value: &Value) -> String {
+    let raw = value
+        .as_str()
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| value.to_string());
+    let needs_quotes = raw.contains([';', '#', '"', '\\', '\n', '\r']) || raw.trim() != raw;
+    if !needs_quotes {
+        return raw;
+    }
+    let escaped = raw
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r");
+    format!("\"{}\"", escaped)
+}
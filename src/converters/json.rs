@@ -1,7 +1,25 @@
 use crate::errors::SmError;
 use serde_json::{Map, Value};
 
-pub fn convert(data: &Map<String, Value>) -> Result<String, SmError> {
-    let obj = Value::Object(data.clone());
-    Ok(serde_json::to_string_pretty(&obj)?)
+pub fn convert(data: &Map<String, Value>, compact: bool) -> Result<String, SmError> {
+    render(&Value::Object(data.clone()), compact)
+}
+
+/// Serialize any JSON value, pretty-printed unless `compact` is set.
+pub fn render(value: &Value, compact: bool) -> Result<String, SmError> {
+    if compact {
+        Ok(serde_json::to_string(value)?)
+    } else {
+        Ok(serde_json::to_string_pretty(value)?)
+    }
+}
+
+/// Stream a JSON value to `writer`, formatted exactly like [`render`].
+pub fn write(writer: impl std::io::Write, value: &Value, compact: bool) -> std::io::Result<()> {
+    if compact {
+        serde_json::to_writer(writer, value)?;
+    } else {
+        serde_json::to_writer_pretty(writer, value)?;
+    }
+    Ok(())
 }
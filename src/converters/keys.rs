@@ -0,0 +1,12 @@
+use super::ConvertOptions;
+use serde_json::{Map, Value};
+
+/// List key names one per line with the values dropped, or as empty `KEY=`
+/// placeholders when `--example` is set, ready for a committed `.env.example`.
+pub fn convert(data: &Map<String, Value>, opts: &ConvertOptions) -> String {
+    let newline = opts.line_ending.as_str();
+    let suffix = if opts.example { "=" } else { "" };
+    super::cased_entries(data, opts)
+        .map(|(key, _)| format!("{}{}{}", opts.key_case.apply(key), suffix, newline))
+        .collect()
+}
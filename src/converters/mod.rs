@@ -1,17 +1,223 @@
+pub mod compose;
 pub mod csv;
+pub mod direnv;
 pub mod env;
+pub mod ini;
 pub mod json;
+pub mod keys;
+pub mod shell;
+pub mod tfvars;
 pub mod yaml;
 
+use crate::detect::array_to_map;
 use crate::errors::SmError;
 use crate::OutputFormat;
+use clap::ValueEnum;
 use serde_json::{Map, Value};
 
-pub fn convert_to_format(data: &Map<String, Value>, format: &OutputFormat) -> Result<String, SmError> {
+/// Placeholder printed instead of a secret value when output is masked.
+pub const MASK: &str = "****";
+
+/// Replace every value with [`MASK`], keeping key names so the shape stays visible.
+pub fn mask_values(data: &Map<String, Value>) -> Map<String, Value> {
+    data.keys()
+        .map(|k| (k.clone(), Value::String(MASK.to_string())))
+        .collect()
+}
+
+/// Line separator for line-oriented formats.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+        }
+    }
+}
+
+/// How JSON booleans are written in env-style output.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq)]
+pub enum BoolStyle {
+    #[default]
+    #[value(name = "true-false")]
+    TrueFalse,
+    #[value(name = "1-0")]
+    OneZero,
+    #[value(name = "yes-no")]
+    YesNo,
+}
+
+impl BoolStyle {
+    pub fn as_str(&self, value: bool) -> &'static str {
+        match (self, value) {
+            (BoolStyle::TrueFalse, true) => "true",
+            (BoolStyle::TrueFalse, false) => "false",
+            (BoolStyle::OneZero, true) => "1",
+            (BoolStyle::OneZero, false) => "0",
+            (BoolStyle::YesNo, true) => "yes",
+            (BoolStyle::YesNo, false) => "no",
+        }
+    }
+}
+
+/// How key names are cased in env-style output.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq)]
+pub enum KeyCase {
+    /// Keys exactly as stored in the secret
+    #[default]
+    Preserve,
+    Upper,
+    Lower,
+}
+
+impl KeyCase {
+    /// `key` in this case. Only ASCII letters change, so a key's length and any
+    /// non-ASCII characters are kept.
+    pub fn apply<'a>(&self, key: &'a str) -> std::borrow::Cow<'a, str> {
+        match self {
+            KeyCase::Preserve => key.into(),
+            KeyCase::Upper => key.to_ascii_uppercase().into(),
+            KeyCase::Lower => key.to_ascii_lowercase().into(),
+        }
+    }
+
+    /// Keys that turn into an earlier key's name in this case, as `(key, earlier
+    /// key)` in map order. Env-style output keeps the earlier key and drops these.
+    pub fn collisions<'a>(&self, data: &'a Map<String, Value>) -> Vec<(&'a str, &'a str)> {
+        if *self == KeyCase::Preserve {
+            return Vec::new();
+        }
+        let mut seen = std::collections::HashMap::new();
+        let mut collisions = Vec::new();
+        for key in data.keys() {
+            match seen.entry(self.apply(key).into_owned()) {
+                std::collections::hash_map::Entry::Occupied(first) => collisions.push((key.as_str(), *first.get())),
+                std::collections::hash_map::Entry::Vacant(slot) => {
+                    slot.insert(key.as_str());
+                }
+            }
+        }
+        collisions
+    }
+}
+
+/// `data`'s entries without the keys [`KeyCase::collisions`] drops, so each
+/// cased key name is written once.
+fn cased_entries<'a>(
+    data: &'a Map<String, Value>,
+    opts: &ConvertOptions,
+) -> impl Iterator<Item = (&'a String, &'a Value)> {
+    let dropped: std::collections::HashSet<&str> =
+        opts.key_case.collisions(data).into_iter().map(|(key, _)| key).collect();
+    data.iter().filter(move |(key, _)| !dropped.contains(key.as_str()))
+}
+
+/// Stringify a value for env-style output: strings as-is, booleans per
+/// `bool_style`, arrays of scalars joined with `env_array_sep`, and anything
+/// else (objects, arrays holding arrays or objects) as JSON.
+pub fn env_value_string(value: &Value, opts: &ConvertOptions) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Bool(b) => opts.bool_style.as_str(*b).to_string(),
+        Value::Array(items) if !items.iter().any(|v| v.is_array() || v.is_object()) => items
+            .iter()
+            .map(|v| env_value_string(v, opts))
+            .collect::<Vec<_>>()
+            .join(&opts.env_array_sep.unwrap_or(',').to_string()),
+        other => other.to_string(),
+    }
+}
+
+/// Format-specific rendering switches taken from the command line.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConvertOptions {
+    /// Emit Docker Compose environment as a mapping instead of a list
+    pub compose_map: bool,
+    /// Line separator for env, stdout, and INI output
+    pub line_ending: LineEnding,
+    /// Write JSON on a single line instead of pretty-printed
+    pub json_compact: bool,
+    /// CSV field delimiter; `None` means a comma
+    pub csv_delimiter: Option<u8>,
+    /// Omit the CSV `key,value` header row
+    pub csv_no_header: bool,
+    /// Pad env keys so the `=` signs line up
+    pub env_align: bool,
+    /// Start every env line with `export `, so the file can also be sourced
+    pub env_export_prefix: bool,
+    /// Double `$` in env values so docker-compose does not interpolate them
+    pub env_escape_dollar: bool,
+    /// How booleans are written in env, stdout, and direnv output
+    pub bool_style: BoolStyle,
+    /// Separator for arrays of scalars in env, stdout, and direnv output; `None` means a comma
+    pub env_array_sep: Option<char>,
+    /// Casing of key names in env, stdout, direnv, and keys output
+    pub key_case: KeyCase,
+    /// Percent-encode env values, for loaders that expect URL-encoded config
+    pub env_url_encode: bool,
+    /// Start YAML output with a `---` document marker
+    pub yaml_doc_start: bool,
+    /// Write YAML in flow style (`{a: 1, b: 2}`) instead of block style
+    pub yaml_flow: bool,
+    /// Replace values with placeholders for a `.env.example`: `KEY=<key>` in env
+    /// and stdout output, `KEY=` instead of bare key names in keys output
+    pub example: bool,
+}
+
+pub fn convert_to_format(
+    data: &Map<String, Value>,
+    format: &OutputFormat,
+    opts: &ConvertOptions,
+) -> Result<String, SmError> {
+    match format {
+        OutputFormat::Stdout | OutputFormat::Env => Ok(env::convert(data, opts)),
+        OutputFormat::Json => json::convert(data, opts.json_compact),
+        OutputFormat::Yaml => yaml::convert(data, opts.yaml_doc_start, opts.yaml_flow),
+        OutputFormat::Csv => csv::convert(data, opts),
+        OutputFormat::Tfvars => Ok(tfvars::convert(data)),
+        OutputFormat::Ini => Ok(ini::convert(data, opts.line_ending)),
+        OutputFormat::ComposeEnv => compose::convert(data, opts.compose_map),
+        OutputFormat::Direnv => Ok(direnv::convert(data, opts)),
+        OutputFormat::Keys => Ok(keys::convert(data, opts)),
+    }
+}
+
+/// Render a JSON array secret. JSON and YAML keep the array shape, stdout prints
+/// one element per line, and the key-value formats flatten it into `<KEY>_<index>` entries.
+pub fn convert_array(
+    items: &[Value],
+    format: &OutputFormat,
+    key: &str,
+    opts: &ConvertOptions,
+) -> Result<String, SmError> {
     match format {
-        OutputFormat::Stdout | OutputFormat::Env => Ok(env::convert(data)),
-        OutputFormat::Json => json::convert(data),
-        OutputFormat::Yaml => yaml::convert(data),
-        OutputFormat::Csv => csv::convert(data),
+        OutputFormat::Json => json::render(&Value::Array(items.to_vec()), opts.json_compact),
+        OutputFormat::Yaml => yaml::render(&Value::Array(items.to_vec()), opts.yaml_doc_start, opts.yaml_flow),
+        OutputFormat::Stdout => {
+            let mut content = String::new();
+            for item in items {
+                let item_str = item
+                    .as_str()
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| item.to_string());
+                content.push_str(&item_str);
+                content.push_str(opts.line_ending.as_str());
+            }
+            Ok(content)
+        }
+        OutputFormat::Env
+        | OutputFormat::Csv
+        | OutputFormat::Tfvars
+        | OutputFormat::Ini
+        | OutputFormat::ComposeEnv
+        | OutputFormat::Direnv
+        | OutputFormat::Keys => convert_to_format(&array_to_map(items, key), format, opts),
     }
 }
@@ -0,0 +1,14 @@
+/// Quote a value for POSIX shells. Values made only of characters that are
+/// never special are left bare; anything else is single-quoted, with embedded
+/// single quotes written as `'\''`.
+pub fn quote(value: &str) -> String {
+    let safe = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_@%+=:,./-".contains(c));
+    if safe {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
@@ -0,0 +1,72 @@
+use serde_json::{Map, Value};
+
+/// Render `key = value` lines. Top-level names must be HCL identifiers, so keys
+/// that are not (see [`invalid_keys`]) are skipped rather than written broken.
+pub fn convert(data: &Map<String, Value>) -> String {
+    let mut content = String::new();
+    for (key, value) in data.iter().filter(|(key, _)| is_valid_identifier(key)) {
+        content.push_str(&format!("{} = {}\n", key, hcl_value(value)));
+    }
+    content
+}
+
+/// Check a key against the HCL identifier rule: a letter or `_`, then letters,
+/// digits, `_`, or `-`. Terraform variable names follow the same rule.
+pub fn is_valid_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Keys [`convert`] skips because they are not HCL identifiers, in map order.
+pub fn invalid_keys(data: &Map<String, Value>) -> Vec<&str> {
+    data.keys()
+        .map(|k| k.as_str())
+        .filter(|k| !is_valid_identifier(k))
+        .collect()
+}
+
+/// Render a JSON value as an HCL expression. Numbers and booleans stay unquoted.
+fn hcl_value(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => format!("\"{}\"", escape_hcl(s)),
+        Value::Array(items) => {
+            let rendered: Vec<String> = items.iter().map(hcl_value).collect();
+            format!("[{}]", rendered.join(", "))
+        }
+        Value::Object(obj) => {
+            let rendered: Vec<String> = obj
+                .iter()
+                .map(|(k, v)| format!("\"{}\" = {}", escape_hcl(k), hcl_value(v)))
+                .collect();
+            format!("{{ {} }}", rendered.join(", "))
+        }
+    }
+}
+
+/// Escape a string for an HCL quoted literal, including template sequences.
+pub fn escape_hcl(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '$' | '%' if chars.peek() == Some(&'{') => {
+                escaped.push(c);
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -1,7 +1,58 @@
 use crate::errors::SmError;
 use serde_json::{Map, Value};
 
-pub fn convert(data: &Map<String, Value>) -> Result<String, SmError> {
-    let obj = Value::Object(data.clone());
-    Ok(serde_yml::to_string(&obj)?)
+/// Explicit document start marker some multi-document YAML pipelines require.
+pub const DOC_START: &str = "---\n";
+
+pub fn convert(data: &Map<String, Value>, doc_start: bool, flow: bool) -> Result<String, SmError> {
+    render(&Value::Object(data.clone()), doc_start, flow)
+}
+
+/// Serialize any JSON value as YAML, optionally led by a `---` marker.
+/// With `flow`, the whole document is written on one line in flow style
+/// (`{a: 1, b: [x, y]}`) instead of serde_yml's block style.
+pub fn render(value: &Value, doc_start: bool, flow: bool) -> Result<String, SmError> {
+    let yaml = if flow {
+        let mut line = to_flow(value)?;
+        line.push('\n');
+        line
+    } else {
+        serde_yml::to_string(value)?
+    };
+    if doc_start && !yaml.starts_with("---") {
+        Ok(format!("{}{}", DOC_START, yaml))
+    } else {
+        Ok(yaml)
+    }
+}
+
+/// Flow-style YAML for `value`. serde_yml only emits block style, so
+/// collections are written here and scalars borrow its plain-vs-quoted choice.
+fn to_flow(value: &Value) -> Result<String, SmError> {
+    Ok(match value {
+        Value::Object(map) => {
+            let entries = map
+                .iter()
+                .map(|(k, v)| Ok(format!("{}: {}", flow_string(k)?, to_flow(v)?)))
+                .collect::<Result<Vec<_>, SmError>>()?;
+            format!("{{{}}}", entries.join(", "))
+        }
+        Value::Array(items) => {
+            let items = items.iter().map(to_flow).collect::<Result<Vec<_>, _>>()?;
+            format!("[{}]", items.join(", "))
+        }
+        Value::String(s) => flow_string(s)?,
+        scalar => scalar.to_string(),
+    })
+}
+
+/// A string scalar, plain when serde_yml would leave it unquoted and it holds no
+/// flow indicators (`,[]{}`); otherwise double-quoted.
+fn flow_string(s: &str) -> Result<String, SmError> {
+    let plain = serde_yml::to_string(s)?;
+    if plain.trim_end_matches('\n') == s && !s.contains([',', '[', ']', '{', '}']) {
+        Ok(s.to_string())
+    } else {
+        Ok(serde_json::to_string(s)?)
+    }
 }
@@ -1,51 +1,240 @@
+use base64::Engine;
 use serde_json::{Map, Value};
 
+/// Default key prefix used when flattening JSON array secrets (`SECRET_0`, `SECRET_1`, ...).
+pub const DEFAULT_ARRAY_KEY: &str = "SECRET";
+
+/// Default key for a plain-text secret that is not in `KEY=value` form.
+pub const DEFAULT_PLAIN_KEY: &str = "SECRET_VALUE";
+
+/// Default key for a binary secret's base64 content.
+pub const DEFAULT_BINARY_KEY: &str = "binary_data";
+
+/// How leading and trailing whitespace in values is handled.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum TrimMode {
+    /// Trim values parsed from `KEY=value` text; keep JSON strings exactly as stored
+    #[default]
+    KeyValueOnly,
+    /// Trim every string value, whatever the secret's format (`--trim-whitespace`)
+    All,
+    /// Keep whitespace everywhere, including in `KEY=value` text (`--no-trim`)
+    None,
+}
+
 pub enum SecretFormat {
     Json(Map<String, Value>),
+    Array(Vec<Value>),
     PlainText(String),
 }
 
 /// Detects the format of a secret string.
-/// Only classifies as JSON if the top-level value is an Object.
-/// Arrays, scalars, and null are treated as plain text.
+/// Top-level objects are classified as JSON and top-level arrays as Array.
+/// Scalars and null are treated as plain text.
 pub fn detect_secret_format(secret: &str) -> SecretFormat {
     match serde_json::from_str::<Value>(secret) {
         Ok(Value::Object(obj)) => SecretFormat::Json(obj),
+        Ok(Value::Array(items)) => SecretFormat::Array(items),
         _ => SecretFormat::PlainText(secret.to_string()),
     }
 }
 
+/// Flatten array elements into `<KEY>_0`, `<KEY>_1`, ... entries.
+pub fn array_to_map(items: &[Value], key: &str) -> Map<String, Value> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (format!("{}_{}", key, i), v.clone()))
+        .collect()
+}
+
 /// Parse key=value text into a Map, skipping blank lines and comments.
+/// Values are trimmed. A repeated key keeps its last value; see [`duplicate_env_keys`].
 pub fn parse_env_vars(text: &str) -> Map<String, Value> {
-    let mut map = Map::new();
-    for line in text.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() || trimmed.starts_with('#') {
+    parse_env_vars_with(text, true)
+}
+
+/// [`parse_env_vars`], keeping the whitespace around values when `trim` is false.
+pub fn parse_env_vars_with(text: &str, trim: bool) -> Map<String, Value> {
+    env_pairs(text, trim)
+        .map(|(key, value)| (key.to_string(), Value::String(unquote_env_value(value))))
+        .collect()
+}
+
+/// Keys that appear more than once in key=value text, in order of first repeat.
+pub fn duplicate_env_keys(text: &str) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    let mut duplicates = Vec::new();
+    for (key, _) in env_pairs(text, true) {
+        if !seen.insert(key) && !duplicates.iter().any(|d| d == key) {
+            duplicates.push(key.to_string());
+        }
+    }
+    duplicates
+}
+
+/// One line of `KEY=value` text, as kept by [`env_layout`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum EnvLine {
+    /// A comment or blank line, without its line ending
+    Verbatim(String),
+    /// A `KEY=value` line; the value itself comes from the parsed map
+    Key(String),
+}
+
+/// The line structure of `KEY=value` text: comments and blank lines in place,
+/// and the key of every entry. Lines [`parse_env_vars`] ignores are left out.
+pub fn env_layout(text: &str) -> Vec<EnvLine> {
+    text.lines()
+        .filter_map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.trim_end().is_empty() || trimmed.starts_with('#') {
+                return Some(EnvLine::Verbatim(line.trim_end_matches('\r').to_string()));
+            }
+            env_pairs(line, true).next().map(|(key, _)| EnvLine::Key(key.to_string()))
+        })
+        .collect()
+}
+
+fn env_pairs(text: &str, trim: bool) -> impl Iterator<Item = (&str, &str)> {
+    text.lines().filter_map(move |line| {
+        let line = line.trim_start();
+        if line.trim_end().is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (key, value) = line.split_once('=')?;
+        // `export KEY=value` lines, as written by --export-prefix
+        let key = key.strip_prefix("export ").unwrap_or(key).trim();
+        let value = if trim { value.trim() } else { value };
+        (!key.is_empty()).then_some((key, value))
+    })
+}
+
+/// Strip surrounding double quotes from a value. Inside a fully quoted value,
+/// `\n`, `\r`, `\"`, and `\\` are unescaped, undoing the env writer's escaping of
+/// multi-line values.
+fn unquote_env_value(value: &str) -> String {
+    let quoted = value.len() >= 2 && value.starts_with('"') && value.ends_with('"');
+    if !quoted || !value.contains('\\') {
+        return value.trim_matches('"').to_string();
+    }
+    let value = &value[1..value.len() - 1];
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
             continue;
         }
-        if let Some(pos) = trimmed.find('=') {
-            let key = trimmed[..pos].trim();
-            let value = trimmed[pos + 1..].trim().trim_matches('"');
-            if !key.is_empty() {
-                map.insert(key.to_string(), Value::String(value.to_string()));
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some(other @ ('"' | '\\')) => out.push(other),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
             }
+            None => out.push('\\'),
         }
     }
-    map
+    out
 }
 
 /// Convert a detected SecretFormat into a key-value Map.
 pub fn secret_to_map(format: SecretFormat) -> Map<String, Value> {
     match format {
         SecretFormat::Json(map) => map,
-        SecretFormat::PlainText(text) => {
-            if text.contains('=') {
-                parse_env_vars(&text)
-            } else {
-                let mut map = Map::new();
-                map.insert("SECRET_VALUE".to_string(), Value::String(text));
-                map
+        SecretFormat::Array(items) => array_to_map(&items, DEFAULT_ARRAY_KEY),
+        SecretFormat::PlainText(text) => plain_text_to_map(text, DEFAULT_PLAIN_KEY, TrimMode::default()),
+    }
+}
+
+/// Parse `KEY=value` text, or store a single opaque value under `key`.
+/// Whitespace is handled according to `trim`. Env text is always parsed rather
+/// than passed through, so comments are dropped and a repeated key keeps its
+/// last value (at the position of its first occurrence).
+pub fn plain_text_to_map(text: String, key: &str, trim: TrimMode) -> Map<String, Value> {
+    let mut map = if text.contains('=') {
+        parse_env_vars_with(&text, trim != TrimMode::None)
+    } else {
+        let mut map = Map::new();
+        map.insert(key.to_string(), Value::String(text));
+        map
+    };
+    if trim == TrimMode::All {
+        map.values_mut().for_each(trim_value);
+    }
+    map
+}
+
+/// Trim surrounding whitespace from every string in `value`, including nested ones.
+pub fn trim_value(value: &mut Value) {
+    match value {
+        Value::String(s) => {
+            let trimmed = s.trim();
+            if trimmed.len() != s.len() {
+                *s = trimmed.to_string();
             }
         }
+        Value::Array(items) => items.iter_mut().for_each(trim_value),
+        Value::Object(map) => map.values_mut().for_each(trim_value),
+        _ => {}
+    }
+}
+
+/// Heuristic check for standard, padded base64: a non-empty string of the base64
+/// alphabet whose length is a multiple of 4 and which decodes cleanly.
+/// Surrounding whitespace is ignored.
+pub fn looks_like_base64(text: &str) -> bool {
+    let trimmed = text.trim();
+    !trimmed.is_empty()
+        && trimmed.len().is_multiple_of(4)
+        && trimmed
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '='))
+        && base64::engine::general_purpose::STANDARD.decode(trimmed).is_ok()
+}
+
+/// Look up a value by key, falling back to a dotted path (`db.password`)
+/// through nested objects. Array elements can be addressed by index (`hosts.0`).
+pub fn select_value<'a>(data: &'a Map<String, Value>, path: &str) -> Option<&'a Value> {
+    if let Some(value) = data.get(path) {
+        return Some(value);
+    }
+    let mut segments = path.split('.');
+    let mut current = data.get(segments.next()?)?;
+    for segment in segments {
+        current = match current {
+            Value::Object(obj) => obj.get(segment)?,
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
     }
+    Some(current)
+}
+
+/// Guess the file type of a binary secret from its leading bytes, returning
+/// `(extension, MIME type)`. PEM and PKCS#12 are checked by hand since they
+/// have no fixed magic number; everything else is left to `infer`.
+pub fn sniff_binary_type(bytes: &[u8]) -> Option<(&'static str, &'static str)> {
+    if bytes.starts_with(b"-----BEGIN ") {
+        return Some(("pem", "application/x-pem-file"));
+    }
+    if is_pkcs12(bytes) {
+        return Some(("p12", "application/x-pkcs12"));
+    }
+    infer::get(bytes).map(|t| (t.extension(), t.mime_type()))
+}
+
+/// A PKCS#12 file is a DER `SEQUENCE` whose first element is `INTEGER 3`.
+fn is_pkcs12(bytes: &[u8]) -> bool {
+    let Some((&0x30, rest)) = bytes.split_first() else {
+        return false;
+    };
+    let body = match rest.first() {
+        Some(&len) if len & 0x80 != 0 => rest.get(1 + usize::from(len & 0x7f)..),
+        Some(_) => rest.get(1..),
+        None => None,
+    };
+    body.is_some_and(|b| b.starts_with(&[0x02, 0x01, 0x03]))
 }
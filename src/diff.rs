@@ -0,0 +1,36 @@
+//! Key-level comparison of a secret against a local file for `sm2env diff`.
+
+use crate::format_value;
+use serde_json::{Map, Value};
+
+/// How a key differs when going from the secret to the local file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyChange {
+    /// Only in the local file
+    Added { value: String },
+    /// Only in the secret
+    Removed { value: String },
+    /// In both, with different values
+    Changed { old: String, new: String },
+}
+
+/// Keys that differ between `remote` (the secret) and `local` (the file), in
+/// the secret's key order followed by keys only in the file. Values are compared
+/// as rendered strings, so `5432` in JSON matches `PORT=5432` in an env file.
+pub fn diff_maps(remote: &Map<String, Value>, local: &Map<String, Value>) -> Vec<(String, KeyChange)> {
+    let mut changes = Vec::new();
+    for (key, value) in remote {
+        let old = format_value(value);
+        match local.get(key).map(format_value) {
+            None => changes.push((key.clone(), KeyChange::Removed { value: old })),
+            Some(new) if new != old => changes.push((key.clone(), KeyChange::Changed { old, new })),
+            Some(_) => {}
+        }
+    }
+    for (key, value) in local {
+        if !remote.contains_key(key) {
+            changes.push((key.clone(), KeyChange::Added { value: format_value(value) }));
+        }
+    }
+    changes
+}
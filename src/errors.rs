@@ -6,6 +6,9 @@ pub enum SmError {
     #[error("AWS error: {0}")]
     AwsError(String),
 
+    #[error("AWS error: {message}")]
+    NotFound { secret: String, message: String },
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -17,6 +20,68 @@ pub enum SmError {
 
     #[error("Path error: {0}")]
     PathError(String),
+
+    #[error("Operation timed out after {0} seconds")]
+    TimeoutError(u64),
+
+    #[error("Clipboard error: {0}")]
+    ClipboardError(String),
+
+    #[error("Keyring error: {0}")]
+    KeyringError(String),
+
+    #[error("Prompt error: {0}")]
+    PromptError(String),
+
+    #[error("Schema validation failed: {0}")]
+    SchemaError(String),
+
+    #[error("Secret and file differ in {0} key(s)")]
+    DriftDetected(usize),
+
+    #[error(
+        "Cannot decrypt secret '{0}': you likely lack kms:Decrypt permission on the KMS key \
+         that encrypts it. Ask for access to the key or check the key policy."
+    )]
+    KmsDecryptError(String),
+}
+
+impl SmError {
+    /// Process exit code for this error. KMS decryption failures get their own
+    /// code so scripts can tell them apart from other AWS errors.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            SmError::KmsDecryptError(_) => 3,
+            _ => 1,
+        }
+    }
+
+    /// Stable name of the error kind for `--error-format json`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            SmError::AwsError(_) => "AwsError",
+            SmError::NotFound { .. } => "NotFound",
+            SmError::IoError(_) => "IoError",
+            SmError::ParseError(_) => "ParseError",
+            SmError::FormatError(_) => "FormatError",
+            SmError::PathError(_) => "PathError",
+            SmError::TimeoutError(_) => "Timeout",
+            SmError::ClipboardError(_) => "ClipboardError",
+            SmError::KeyringError(_) => "KeyringError",
+            SmError::PromptError(_) => "PromptError",
+            SmError::SchemaError(_) => "SchemaError",
+            SmError::DriftDetected(_) => "DriftDetected",
+            SmError::KmsDecryptError(_) => "KmsDecryptError",
+        }
+    }
+
+    /// The secret the error is about, when it is known.
+    pub fn secret(&self) -> Option<&str> {
+        match self {
+            SmError::NotFound { secret, .. } | SmError::KmsDecryptError(secret) => Some(secret),
+            _ => None,
+        }
+    }
 }
 
 impl From<serde_json::Error> for SmError {
@@ -0,0 +1,78 @@
+//! Provenance header for generated files (`get --header`).
+
+use crate::converters::LineEnding;
+use crate::OutputFormat;
+use serde_json::{json, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Key added to JSON output, which has no comment syntax.
+pub const META_KEY: &str = "_sm2env_meta";
+
+/// Where a generated file came from and when.
+pub struct Header<'a> {
+    pub secret_names: &'a [String],
+    pub generated_at: SystemTime,
+}
+
+impl Header<'_> {
+    /// `generated by sm2env <version> from secret <names> at <timestamp>`
+    pub fn text(&self) -> String {
+        format!(
+            "generated by sm2env {} from secret {} at {}",
+            env!("CARGO_PKG_VERSION"),
+            self.secret_names.join(", "),
+            format_timestamp(self.generated_at)
+        )
+    }
+
+    /// The same information as a JSON object for [`META_KEY`].
+    pub fn meta(&self) -> Value {
+        json!({
+            "generator": format!("sm2env {}", env!("CARGO_PKG_VERSION")),
+            "secrets": self.secret_names,
+            "generated_at": format_timestamp(self.generated_at),
+        })
+    }
+
+    /// Prepend the header as a comment line for formats that have comments.
+    /// JSON and CSV content is returned unchanged.
+    pub fn prepend(&self, content: String, format: &OutputFormat, line_ending: LineEnding) -> String {
+        match comment_marker(format) {
+            Some(marker) => format!("{} {}{}{}", marker, self.text(), line_ending.as_str(), content),
+            None => content,
+        }
+    }
+}
+
+fn comment_marker(format: &OutputFormat) -> Option<&'static str> {
+    match format {
+        OutputFormat::Ini => Some(";"),
+        OutputFormat::Json | OutputFormat::Csv => None,
+        OutputFormat::Stdout
+        | OutputFormat::Env
+        | OutputFormat::Yaml
+        | OutputFormat::Tfvars
+        | OutputFormat::ComposeEnv
+        | OutputFormat::Direnv
+        | OutputFormat::Keys => Some("#"),
+    }
+}
+
+/// Format a time as UTC RFC 3339 with second precision, e.g. `2024-05-01T12:00:00Z`.
+pub fn format_timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    let (year, month, day) = civil_from_days(days as i64);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+// Shared with build.rs, which stamps the build date into `--version`
+include!("civil_date.rs");
@@ -0,0 +1,21 @@
+//! OS keychain storage for `get --keyring`, built with the `keyring` feature.
+
+use sm2env::errors::SmError;
+
+/// Store `secret` in the OS keychain under `service`/`account`, replacing any
+/// existing entry.
+#[cfg(feature = "keyring")]
+pub fn store(service: &str, account: &str, secret: &str) -> Result<(), SmError> {
+    let entry =
+        keyring::Entry::new(service, account).map_err(|e| SmError::KeyringError(e.to_string()))?;
+    entry
+        .set_password(secret)
+        .map_err(|e| SmError::KeyringError(e.to_string()))
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn store(_service: &str, _account: &str, _secret: &str) -> Result<(), SmError> {
+    Err(SmError::KeyringError(
+        "sm2env was built without keyring support; rebuild with `--features keyring`".to_string(),
+    ))
+}
@@ -0,0 +1,210 @@
+//! Core of `sm2env`: detect the shape of an AWS Secrets Manager secret and
+//! render it as env, JSON, YAML, CSV, and other formats.
+//!
+//! The `sm2env` binary is a thin CLI over this crate. Formatting functions
+//! return `String`s; writing files is left to [`output`].
+
+pub mod aws_client;
+pub mod cache;
+pub mod config;
+pub mod converters;
+pub mod detect;
+pub mod diff;
+pub mod errors;
+pub mod header;
+pub mod output;
+pub mod schema;
+pub mod template;
+
+use clap::ValueEnum;
+use converters::ConvertOptions;
+use detect::{detect_secret_format, parse_env_vars, secret_to_map, SecretFormat, DEFAULT_ARRAY_KEY};
+use errors::SmError;
+use serde_json::{Map, Value};
+use std::fmt;
+
+#[derive(ValueEnum, Clone, Debug)]
+pub enum OutputFormat {
+    Stdout,
+    Json,
+    Env,
+    Yaml,
+    Csv,
+    Tfvars,
+    Ini,
+    ComposeEnv,
+    Direnv,
+    /// Key names only, without values
+    Keys,
+}
+
+impl OutputFormat {
+    /// File name used when no `--file` is given.
+    pub fn default_filename(&self) -> &'static str {
+        match self {
+            OutputFormat::Json => "secret.json",
+            OutputFormat::Yaml => "secret.yaml",
+            OutputFormat::Csv => "secret.csv",
+            OutputFormat::Tfvars => "secret.auto.tfvars",
+            OutputFormat::Ini => "secret.ini",
+            OutputFormat::ComposeEnv => "compose.env.yaml",
+            OutputFormat::Direnv => ".envrc",
+            OutputFormat::Keys => ".env.example",
+            OutputFormat::Stdout | OutputFormat::Env => ".env",
+        }
+    }
+
+    /// Format implied by a `--file` path when `--output` is not given: `.json`,
+    /// `.yaml`/`.yml`, `.csv`, `.tfvars`, `.ini`, `.env` (including `.env` and
+    /// `.env.<suffix>` files), and `.envrc` for direnv. `None` for anything else.
+    pub fn from_path(path: &std::path::Path) -> Option<OutputFormat> {
+        let name = path.file_name()?.to_str()?;
+        if name == ".envrc" {
+            return Some(OutputFormat::Direnv);
+        }
+        if name == ".env" || name.starts_with(".env.") {
+            return Some(OutputFormat::Env);
+        }
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "json" => Some(OutputFormat::Json),
+            "yaml" | "yml" => Some(OutputFormat::Yaml),
+            "csv" => Some(OutputFormat::Csv),
+            "tfvars" => Some(OutputFormat::Tfvars),
+            "ini" => Some(OutputFormat::Ini),
+            "env" => Some(OutputFormat::Env),
+            "envrc" => Some(OutputFormat::Direnv),
+            _ => None,
+        }
+    }
+
+    /// Extension used for per-secret files such as `get --all` output.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Json => "json",
+            OutputFormat::Yaml => "yaml",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Tfvars => "auto.tfvars",
+            OutputFormat::Ini => "ini",
+            OutputFormat::ComposeEnv => "compose.yaml",
+            OutputFormat::Direnv => "envrc",
+            OutputFormat::Keys => "env.example",
+            OutputFormat::Stdout | OutputFormat::Env => "env",
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Stdout => write!(f, "stdout"),
+            OutputFormat::Json => write!(f, "json"),
+            OutputFormat::Env => write!(f, "env"),
+            OutputFormat::Yaml => write!(f, "yaml"),
+            OutputFormat::Csv => write!(f, "csv"),
+            OutputFormat::Tfvars => write!(f, "tfvars"),
+            OutputFormat::Ini => write!(f, "ini"),
+            OutputFormat::ComposeEnv => write!(f, "compose-env"),
+            OutputFormat::Direnv => write!(f, "direnv"),
+            OutputFormat::Keys => write!(f, "keys"),
+        }
+    }
+}
+
+/// Detect the shape of a secret string and render it in `format`.
+/// JSON arrays keep their shape for json/yaml/stdout and are flattened to
+/// `SECRET_<index>` keys for key-value formats.
+pub fn format_secret(secret: &str, format: &OutputFormat, opts: &ConvertOptions) -> Result<String, SmError> {
+    match detect_secret_format(secret) {
+        SecretFormat::Array(items) => converters::convert_array(&items, format, DEFAULT_ARRAY_KEY, opts),
+        fmt => converters::convert_to_format(&secret_to_map(fmt), format, opts),
+    }
+}
+
+/// Convert `KEY=value` text into a pretty-printed JSON object.
+pub fn convert_env_to_json(text: &str) -> Result<String, SmError> {
+    converters::json::convert(&parse_env_vars(text), false)
+}
+
+/// Keep only `keys`, in the order given. Returns the filtered map and the
+/// requested keys that were not present.
+pub fn filter_keys(mut data: Map<String, Value>, keys: &[&str]) -> (Map<String, Value>, Vec<String>) {
+    let mut filtered = Map::new();
+    let mut missing = Vec::new();
+    for key in keys {
+        match data.remove(*key) {
+            Some(val) => {
+                filtered.insert(key.to_string(), val);
+            }
+            None => missing.push(key.to_string()),
+        }
+    }
+    (filtered, missing)
+}
+
+/// Keep keys matching any `include` glob (all keys when `include` is empty),
+/// then drop keys matching any `exclude` glob. Patterns are case-sensitive.
+pub fn include_exclude_keys(
+    data: Map<String, Value>,
+    include: &[String],
+    exclude: &[String],
+) -> Result<Map<String, Value>, SmError> {
+    let include = compile_globs(include)?;
+    let exclude = compile_globs(exclude)?;
+    Ok(data
+        .into_iter()
+        .filter(|(k, _)| include.is_empty() || include.is_match(k))
+        .filter(|(k, _)| !exclude.is_match(k))
+        .collect())
+}
+
+/// Sort keys alphabetically at every nesting level, for `--sort-keys`. Without
+/// it, output keeps the order of the original secret.
+pub fn sort_keys(mut data: Map<String, Value>) -> Map<String, Value> {
+    data.sort_keys();
+    data.values_mut().for_each(Value::sort_all_objects);
+    data
+}
+
+fn compile_globs(patterns: &[String]) -> Result<globset::GlobSet, SmError> {
+    let mut builder = globset::GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = globset::Glob::new(pattern)
+            .map_err(|e| SmError::ParseError(format!("invalid glob pattern '{}': {}", pattern, e)))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| SmError::ParseError(e.to_string()))
+}
+
+/// Prepend `prefix` to every key.
+pub fn prefix_keys(data: Map<String, Value>, prefix: &str) -> Map<String, Value> {
+    data.into_iter().map(|(k, v)| (format!("{}{}", prefix, k), v)).collect()
+}
+
+/// Keys whose value, rendered as by [`format_value`], is longer than `limit`
+/// bytes, with that length, for `--max-value-length`.
+pub fn oversized_values(data: &Map<String, Value>, limit: usize) -> Vec<(&str, usize)> {
+    data.iter()
+        .map(|(key, value)| (key.as_str(), format_value(value).len()))
+        .filter(|(_, len)| *len > limit)
+        .collect()
+}
+
+/// Nest secret data for `--wrap` as `{"name": ..., "version": ..., "data": ...}`.
+/// `version` is left out when it is not known, e.g. for merged secrets.
+pub fn wrap_json(data: Value, name: &str, version: Option<&str>) -> Map<String, Value> {
+    let mut doc = Map::new();
+    doc.insert("name".to_string(), Value::String(name.to_string()));
+    if let Some(version) = version {
+        doc.insert("version".to_string(), Value::String(version.to_string()));
+    }
+    doc.insert("data".to_string(), data);
+    doc
+}
+
+/// Render a single value for `--select`: strings are printed raw, anything
+/// else as compact JSON.
+pub fn format_value(value: &Value) -> String {
+    value.as_str().map(|s| s.to_string()).unwrap_or_else(|| value.to_string())
+}
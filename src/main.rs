@@ -1,62 +1,233 @@
-mod aws_client;
-mod config;
-mod converters;
-mod detect;
-mod errors;
-mod output;
+mod clipboard;
+mod keychain;
+mod picker;
 mod tests;
+mod ui;
 
 use base64::Engine;
+use sm2env::{
+    aws_client, cache, config, converters, detect, errors, output, template, OutputFormat,
+};
 
 struct GetOptions<'a> {
     secret_names: &'a [String],
-    output_format: &'a OutputFormat,
+    output_formats: &'a [OutputFormat],
     file: Option<&'a str>,
+    output_dir: Option<&'a str>,
+    secure_dir: bool,
     version_stage: &'a str,
     prefix: Option<&'a str>,
     keys: Option<&'a str>,
+    include: &'a [String],
+    exclude: &'a [String],
     dry_run: bool,
     append: bool,
     merge: bool,
+    on_conflict: Option<OnConflict>,
+    array_key: &'a str,
+    key_name: Option<&'a str>,
+    template: Option<&'a str>,
+    interpolate: bool,
+    decode_base64: bool,
+    detect_type: bool,
+    allow_missing: bool,
+    reveal: bool,
+    select: Option<&'a str>,
+    cache: Option<&'a cache::Cache>,
+    strict: bool,
+    name_stem: Option<&'a str>,
+    convert: converters::ConvertOptions,
+    concat: bool,
+    env_suffix: Option<&'a str>,
+    clipboard: bool,
+    header: bool,
+    max_concurrency: usize,
+    sort_keys: bool,
+    keep_comments: bool,
+    max_value_length: Option<usize>,
+    uppercase_keys: bool,
+    report_keys: bool,
+    with_kms_info: bool,
+    fifo_timeout: std::time::Duration,
+    schema: Option<&'a jsonschema::Validator>,
+    keyring: Option<&'a str>,
+    to_ssm: Option<SsmTarget<'a>>,
+    trim: TrimMode,
+    wrap: bool,
+}
+
+/// `get`'s defaults: the flags all off, AWSCURRENT, no cache, and the default
+/// array key, concurrency, and FIFO timeout. Callers set the fields they need.
+impl Default for GetOptions<'_> {
+    fn default() -> Self {
+        GetOptions {
+            secret_names: &[],
+            output_formats: &[],
+            file: None,
+            output_dir: None,
+            secure_dir: false,
+            version_stage: "AWSCURRENT",
+            prefix: None,
+            keys: None,
+            include: &[],
+            exclude: &[],
+            dry_run: false,
+            append: false,
+            merge: false,
+            on_conflict: None,
+            array_key: detect::DEFAULT_ARRAY_KEY,
+            key_name: None,
+            template: None,
+            interpolate: false,
+            decode_base64: false,
+            detect_type: false,
+            allow_missing: false,
+            reveal: false,
+            select: None,
+            cache: None,
+            strict: false,
+            name_stem: None,
+            convert: converters::ConvertOptions::default(),
+            concat: false,
+            env_suffix: None,
+            clipboard: false,
+            header: false,
+            max_concurrency: aws_client::DEFAULT_MAX_CONCURRENCY,
+            sort_keys: false,
+            keep_comments: false,
+            max_value_length: None,
+            uppercase_keys: false,
+            report_keys: false,
+            with_kms_info: false,
+            fifo_timeout: std::time::Duration::from_secs(output::DEFAULT_FIFO_TIMEOUT_SECS),
+            schema: None,
+            keyring: None,
+            to_ssm: None,
+            trim: TrimMode::default(),
+            wrap: false,
+        }
+    }
 }
 use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use clap_complete::{generate, Shell};
-use detect::{detect_secret_format, parse_env_vars, secret_to_map};
+use detect::{
+    array_to_map, detect_secret_format, parse_env_vars, secret_to_map, select_value, SecretFormat,
+    TrimMode,
+};
+use aws_sdk_secretsmanager::types::SecretListEntry;
 use errors::SmError;
 use serde_json::{Map, Value};
-use std::fmt;
-use std::io;
+use std::io::{self, IsTerminal};
+
+/// `--version` output: the package version plus the git commit and build date
+/// recorded by `build.rs`. `-V` prints the version alone.
+const LONG_VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    " (commit ",
+    env!("SM2ENV_GIT_HASH"),
+    ", built ",
+    env!("SM2ENV_BUILD_DATE"),
+    ")"
+);
 
 #[derive(Parser)]
 #[command(
     name = "sm2env",
     about = "A CLI tool to fetch AWS Secrets Manager secrets and save them as .env files.",
-    version = "0.2.0",
+    version,
+    long_version = LONG_VERSION,
     author = "Your Name",
     long_about = "sm2env is a command-line tool that helps retrieve secrets from AWS Secrets Manager \
                   and store them in a .env file for easy environment variable management."
 )]
 struct Cli {
+    /// Suppress informational messages; only data and errors are printed
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Print timing and size diagnostics to stderr (same as --log-level debug)
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Log level for warnings and diagnostics on stderr; overrides RUST_LOG
+    /// (default: info, or warn with --quiet)
+    #[arg(long, global = true, value_enum)]
+    log_level: Option<LogLevel>,
+
+    /// Disable colored output (also honors the NO_COLOR environment variable)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// How a fatal error is printed to stderr
+    #[arg(long, global = true, value_enum, default_value_t = ErrorFormat::Text)]
+    error_format: ErrorFormat,
+
+    /// Abort if the whole operation takes longer than this many seconds
+    #[arg(long, global = true)]
+    timeout: Option<u64>,
+
+    /// Read the shared AWS config from this file instead of ~/.aws/config
+    #[arg(long, global = true, value_name = "PATH")]
+    aws_config_file: Option<String>,
+
+    /// Read AWS credentials from this file instead of ~/.aws/credentials
+    #[arg(long, global = true, value_name = "PATH")]
+    aws_credentials_file: Option<String>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum Commands {
     /// Fetch one or more secrets and save them in the specified format
+    #[command(group(clap::ArgGroup::new("substitution").args(["template", "interpolate"]).multiple(true)))]
     Get {
-        /// One or more secret names to retrieve
-        #[arg(required = true)]
+        /// One or more secret names to retrieve ("-" reads the name from stdin)
+        #[arg(required_unless_present_any = ["all", "stdin", "batch_file", "interactive"])]
         secret_names: Vec<String>,
 
-        /// Output format (stdout, json, env, yaml, csv)
-        #[arg(short, long, value_enum, default_value_t = OutputFormat::Env)]
-        output: OutputFormat,
+        /// Read the secret name from stdin
+        #[arg(long, conflicts_with_all = ["secret_names", "all"])]
+        stdin: bool,
+
+        /// Fetch every secret matching --filter, writing each to <name>.<format>
+        #[arg(long, conflicts_with_all = ["secret_names", "file", "merge", "select", "template", "append"])]
+        all: bool,
+
+        /// Fetch every secret named in this file (one per line, # comments allowed),
+        /// writing each to <name>.<format>
+        #[arg(long, value_name = "PATH", conflicts_with_all = [
+            "secret_names", "stdin", "all", "filter", "file", "merge", "select", "template", "append",
+        ])]
+        batch_file: Option<String>,
+
+        /// Pick the secret from a fuzzy-searchable list (narrowed by --filter)
+        #[arg(short, long, conflicts_with_all = ["secret_names", "stdin", "all", "batch_file"])]
+        interactive: bool,
+
+        /// Filter secrets by name (case-insensitive) when using --all or --interactive
+        #[arg(long, conflicts_with = "secret_names")]
+        filter: Option<String>,
+
+        /// Output format (stdout, json, env, yaml, csv, tfvars, ini, compose-env, direnv, keys); repeat to write several formats.
+        /// Default: inferred from the --file extension, else env
+        #[arg(short, long, value_enum, env = "SM2ENV_OUTPUT")]
+        output: Vec<OutputFormat>,
 
         /// File path to write the output to
         #[arg(short, long)]
         file: Option<String>,
 
+        /// Directory to write each format's default file into
+        #[arg(long, conflicts_with = "file")]
+        output_dir: Option<String>,
+
+        /// Restrict the --output-dir directory to its owner (0700), like its 0600 files (Unix only)
+        #[arg(long, requires = "output_dir")]
+        secure_dir: bool,
+
         /// Override the AWS region
         #[arg(long)]
         region: Option<String>,
@@ -77,6 +248,14 @@ enum Commands {
         #[arg(long)]
         keys: Option<String>,
 
+        /// Keep only keys matching these glob patterns (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        include: Vec<String>,
+
+        /// Drop keys matching these glob patterns (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        exclude: Vec<String>,
+
         /// Print output to stdout without writing any file
         #[arg(long)]
         dry_run: bool,
@@ -88,13 +267,465 @@ enum Commands {
         /// Merge multiple secrets into one output (required when >1 secret name)
         #[arg(long)]
         merge: bool,
+
+        /// Merge every fetched secret (names, --all, or --batch-file) into one JSON object in this file
+        #[arg(long, value_name = "PATH", conflicts_with_all = [
+            "output", "file", "output_dir", "template", "select", "append", "concat", "clipboard", "keyring",
+        ])]
+        merge_into: Option<String>,
+
+        /// Instead of writing output, show which keys differ between the secret's
+        /// AWSPREVIOUS and AWSCURRENT versions (values hidden unless --reveal)
+        #[arg(long, conflicts_with_all = [
+            "stdin", "all", "batch_file", "interactive", "merge", "merge_into", "output", "file", "output_dir",
+            "template", "select", "append", "concat", "clipboard", "keyring", "to_ssm", "version_stage",
+        ])]
+        compare_stages: bool,
+
+        /// What to do when merged secrets share a key; the last value wins unless this is `error`
+        /// [default with --merge-into: warn]
+        #[arg(long, value_enum)]
+        on_conflict: Option<OnConflict>,
+
+        /// Key prefix for JSON array elements in env/csv output (SECRET_0, SECRET_1, ...)
+        #[arg(long, default_value = detect::DEFAULT_ARRAY_KEY)]
+        array_key: String,
+
+        /// Key for a plain-text (default SECRET_VALUE) or binary (default binary_data) secret
+        #[arg(long)]
+        key_name: Option<String>,
+
+        /// Render a template file, replacing ${KEY} placeholders with secret values
+        #[arg(long)]
+        template: Option<String>,
+
+        /// Resolve ${KEY} references inside secret values from other keys of the secret
+        #[arg(long)]
+        interpolate: bool,
+
+        /// Decode string secrets that look like base64 before detecting their format
+        #[arg(long)]
+        decode_base64: bool,
+
+        /// Report the file type (PNG, PDF, PEM, PKCS#12, ...) of a binary secret on stderr
+        #[arg(long)]
+        detect_type: bool,
+
+        /// Leave unresolved ${KEY} placeholders or references blank instead of failing
+        #[arg(long, requires = "substitution")]
+        allow_missing: bool,
+
+        /// Show secret values when printing env/stdout output to the terminal
+        #[arg(long)]
+        reveal: bool,
+
+        /// Maximum retries on throttling or transient AWS errors
+        #[arg(long, default_value_t = aws_client::DEFAULT_MAX_RETRIES)]
+        max_retries: u32,
+
+        /// Print only the value of one key (dotted paths reach nested keys, e.g. db.password)
+        #[arg(long, conflicts_with_all = ["keys", "template", "append", "file", "output_dir"])]
+        select: Option<String>,
+
+        /// Reuse locally cached secrets fetched within this many seconds
+        #[arg(long)]
+        cache_ttl: Option<u64>,
+
+        /// Bypass the local cache, including a cache_ttl set in ~/.sm2env
+        #[arg(long, conflicts_with = "cache_ttl")]
+        no_cache: bool,
+
+        /// Fetch fresh values and overwrite their cached copies, even if still within the TTL
+        #[arg(long, conflicts_with = "no_cache")]
+        refresh_cache: bool,
+
+        /// Fail instead of warning on invalid environment variable names, duplicate
+        /// keys in a KEY=value secret, keys that collide after --key-case, or an
+        /// empty secret
+        #[arg(long)]
+        strict: bool,
+
+        /// Emit compose-env output as a KEY: value mapping instead of a KEY=value list
+        #[arg(long)]
+        compose_map: bool,
+
+        /// Append env output to the target file as-is (no de-duplication, unlike --append)
+        #[arg(long, conflicts_with = "append")]
+        concat: bool,
+
+        /// Suffix for the default env filename, e.g. "local" writes .env.local
+        #[arg(long)]
+        env_suffix: Option<String>,
+
+        /// Copy output that would be printed (e.g. with --select) to the clipboard instead
+        #[arg(long, conflicts_with_all = ["file", "output_dir", "dry_run", "concat", "all", "batch_file"])]
+        clipboard: bool,
+
+        /// Store the secret in the OS keychain under this service name (account: the
+        /// secret name) instead of writing output
+        #[arg(
+            long,
+            value_name = "SERVICE",
+            conflicts_with_all = [
+                "file", "output_dir", "dry_run", "clipboard", "all", "batch_file", "select",
+                "template", "append", "concat",
+            ]
+        )]
+        keyring: Option<String>,
+
+        /// Write each key as a SecureString parameter under this SSM Parameter Store
+        /// path (e.g. /myapp/) instead of writing output
+        #[arg(
+            long,
+            value_name = "PATH",
+            conflicts_with_all = [
+                "file", "output_dir", "dry_run", "clipboard", "all", "batch_file", "select",
+                "template", "append", "concat", "keyring",
+            ]
+        )]
+        to_ssm: Option<String>,
+
+        /// Start generated files with a provenance comment (a _sm2env_meta key for JSON)
+        #[arg(long)]
+        header: bool,
+
+        /// How JSON booleans are written in env, stdout, and direnv output
+        #[arg(long, value_enum, default_value_t = converters::BoolStyle::TrueFalse)]
+        bool_style: converters::BoolStyle,
+
+        /// Separator for arrays of scalars in env, stdout, and direnv output (default: `,`)
+        #[arg(long, value_name = "CHAR")]
+        array_sep: Option<char>,
+
+        /// Case of key names in env, stdout, direnv, and keys output
+        #[arg(long, value_enum, default_value_t = converters::KeyCase::Preserve)]
+        key_case: converters::KeyCase,
+
+        /// Line ending for env, stdout, and INI output
+        #[arg(long, value_enum, default_value_t = converters::LineEnding::Lf)]
+        line_ending: converters::LineEnding,
+
+        /// Write JSON output minified on a single line
+        #[arg(long)]
+        json_compact: bool,
+
+        /// Nest JSON output as {"name", "version", "data"} instead of the bare secret
+        #[arg(long)]
+        wrap: bool,
+
+        /// CSV field delimiter: one ASCII character, or `\t` for tab-separated output
+        #[arg(long, value_parser = converters::csv::parse_delimiter)]
+        delimiter: Option<u8>,
+
+        /// Omit the CSV key,value header row
+        #[arg(long)]
+        no_header: bool,
+
+        /// Align env output into columns (`KEY   = value`) for human readers
+        #[arg(long, alias = "pretty-env")]
+        align: bool,
+
+        /// Start every env line with `export ` (values stay unquoted; see --output direnv for shell quoting)
+        #[arg(long)]
+        export_prefix: bool,
+
+        /// Trim surrounding whitespace from every value, JSON strings included
+        /// (by default only KEY=value secrets are trimmed)
+        #[arg(long, conflicts_with = "no_trim")]
+        trim_whitespace: bool,
+
+        /// Keep surrounding whitespace in every value, KEY=value secrets included
+        #[arg(long)]
+        no_trim: bool,
+
+        /// Escape `$` as `$$` in env values so docker-compose keeps them literal
+        #[arg(long)]
+        escape_dollar: bool,
+
+        /// Percent-encode env values (e.g. `p@ss word` becomes `p%40ss%20word`)
+        #[arg(long)]
+        url_encode: bool,
+
+        /// Start YAML output with a `---` document marker
+        #[arg(long)]
+        yaml_doc_start: bool,
+
+        /// Write YAML output in flow style (`{a: 1, b: 2}`) instead of block style
+        #[arg(long)]
+        yaml_flow: bool,
+
+        /// Sort keys alphabetically instead of keeping the secret's order
+        #[arg(long)]
+        sort_keys: bool,
+
+        /// Keep the comment and blank lines of a plain-text KEY=value secret in
+        /// env output, in their original positions
+        #[arg(long, conflicts_with_all = ["sort_keys", "template", "merge"])]
+        keep_comments: bool,
+
+        /// Fail if any value is longer than this many bytes (default: no limit)
+        #[arg(long, value_name = "BYTES")]
+        max_value_length: Option<usize>,
+
+        /// Rewrite keys as shell-safe names, e.g. api-key becomes API_KEY
+        #[arg(long)]
+        uppercase_keys: bool,
+
+        /// With --uppercase-keys, print the renamed and dropped keys to stderr
+        #[arg(long, requires = "uppercase_keys")]
+        report_keys: bool,
+
+        /// Fail unless each fetched secret is JSON matching this JSON Schema file
+        #[arg(long, value_name = "PATH")]
+        schema: Option<String>,
+
+        /// Seconds to wait for a reader when --file is a named pipe (FIFO)
+        #[arg(long, value_name = "SECONDS", default_value_t = output::DEFAULT_FIFO_TIMEOUT_SECS)]
+        fifo_timeout: u64,
+
+        /// Print which KMS key encrypts each secret to stderr (one extra DescribeSecret call per secret)
+        #[arg(long)]
+        with_kms_info: bool,
+
+        /// Write placeholders instead of values for a .env.example: `KEY=<key>` with
+        /// env or stdout output, `KEY=` with --output keys
+        #[arg(long)]
+        example: bool,
+
+        /// Maximum number of secrets fetched at once with --all, --batch-file, or --merge
+        #[arg(
+            long,
+            default_value_t = aws_client::DEFAULT_MAX_CONCURRENCY,
+            value_parser = parse_max_concurrency
+        )]
+        max_concurrency: usize,
     },
     /// List all available secrets
     List {
-        /// Filter secrets by name (case-insensitive)
+        /// Filter secrets by name (case-insensitive unless --case-sensitive)
         #[arg(short, long)]
         filter: Option<String>,
 
+        /// Match --filter case-sensitively
+        #[arg(long, requires = "filter")]
+        case_sensitive: bool,
+
+        /// Match --filter ignoring case. This is already the default; the flag makes
+        /// it explicit. There is no `-i` short form, which is --interactive
+        #[arg(long, requires = "filter", conflicts_with = "case_sensitive")]
+        ignore_case: bool,
+
+        /// Treat --filter as a glob pattern (e.g. `prod/*/db`) matched against the whole name
+        #[arg(long, requires = "filter")]
+        glob: bool,
+
+        /// Sort order for the listed secrets (dates sort oldest first)
+        #[arg(long, value_enum, default_value_t = SortBy::Name)]
+        sort_by: SortBy,
+
+        /// Keep the order returned by AWS instead of sorting
+        #[arg(long, conflicts_with = "sort_by")]
+        no_sort: bool,
+
+        /// Only list secrets with this tag, as `key=value` or just `key` (repeatable)
+        #[arg(long = "tag", value_name = "KEY[=VALUE]", value_parser = parse_tag_filter)]
+        tags: Vec<TagFilter>,
+
+        /// Only list secrets changed within this long, e.g. `90m`, `24h`, `7d`
+        #[arg(long, value_name = "DURATION", value_parser = parse_since)]
+        since: Option<std::time::Duration>,
+
+        /// Override the AWS region (`all` is the same as --all-regions)
+        #[arg(long)]
+        region: Option<String>,
+
+        /// List secrets in every Secrets Manager region, grouped by region
+        #[arg(long, conflicts_with = "region")]
+        all_regions: bool,
+
+        /// Pick one secret from a fuzzy-searchable list and print its name
+        #[arg(short, long, conflicts_with = "all_regions")]
+        interactive: bool,
+
+        /// Print only the number of matching secrets
+        #[arg(long, conflicts_with = "interactive")]
+        count: bool,
+
+        /// Resume a listing from a pagination token, as printed by --verbose when
+        /// a listing fails part way (only secrets from that page on are listed)
+        #[arg(long, value_name = "TOKEN", conflicts_with = "all_regions")]
+        start_token: Option<String>,
+
+        /// Show the list through `$PAGER` (default `less`) when stdout is a terminal
+        #[arg(long, overrides_with = "no_pager", conflicts_with_all = ["interactive", "count"])]
+        paginate: bool,
+
+        /// Print the list directly, even after --paginate (e.g. from a shell alias)
+        #[arg(long, overrides_with = "paginate")]
+        no_pager: bool,
+
+        /// Use a named AWS credentials profile
+        #[arg(long)]
+        profile: Option<String>,
+
+        /// Maximum retries on throttling or transient AWS errors
+        #[arg(long, default_value_t = aws_client::DEFAULT_MAX_RETRIES)]
+        max_retries: u32,
+    },
+    /// Show a secret's metadata without fetching its value
+    Describe {
+        /// Name or ARN of the secret to describe
+        secret_name: String,
+
+        /// Output format (text, json)
+        #[arg(short, long, value_enum, default_value_t = MetadataFormat::Text)]
+        output: MetadataFormat,
+
+        /// Override the AWS region
+        #[arg(long)]
+        region: Option<String>,
+
+        /// Use a named AWS credentials profile
+        #[arg(long)]
+        profile: Option<String>,
+
+        /// Maximum retries on throttling or transient AWS errors
+        #[arg(long, default_value_t = aws_client::DEFAULT_MAX_RETRIES)]
+        max_retries: u32,
+    },
+    /// Trigger rotation of a secret
+    Rotate {
+        /// Name or ARN of the secret to rotate
+        secret_name: String,
+
+        /// ARN of the Lambda function that performs the rotation
+        #[arg(long)]
+        rotation_lambda_arn: Option<String>,
+
+        /// Rotate now instead of waiting for the next scheduled window
+        #[arg(long)]
+        immediate: bool,
+
+        /// Override the AWS region
+        #[arg(long)]
+        region: Option<String>,
+
+        /// Use a named AWS credentials profile
+        #[arg(long)]
+        profile: Option<String>,
+
+        /// Maximum retries on throttling or transient AWS errors
+        #[arg(long, default_value_t = aws_client::DEFAULT_MAX_RETRIES)]
+        max_retries: u32,
+    },
+    /// Delete a secret (scheduled with a recovery window by default)
+    Delete {
+        /// Name or ARN of the secret to delete
+        secret_name: String,
+
+        /// Delete immediately without a recovery window
+        #[arg(long, visible_alias = "no-recovery", conflicts_with = "recovery_days")]
+        force: bool,
+
+        /// Days before the secret is permanently deleted (7-30, AWS default: 30)
+        #[arg(long, value_parser = clap::value_parser!(i64).range(7..=30))]
+        recovery_days: Option<i64>,
+
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Override the AWS region
+        #[arg(long)]
+        region: Option<String>,
+
+        /// Use a named AWS credentials profile
+        #[arg(long)]
+        profile: Option<String>,
+
+        /// Maximum retries on throttling or transient AWS errors
+        #[arg(long, default_value_t = aws_client::DEFAULT_MAX_RETRIES)]
+        max_retries: u32,
+    },
+    /// Compare a secret with a local env or JSON file, key by key
+    Diff {
+        /// Name or ARN of the secret to compare
+        secret_name: String,
+
+        /// Local file to compare against (KEY=value or JSON)
+        #[arg(short, long)]
+        file: String,
+
+        /// Secret version stage (default: AWSCURRENT)
+        #[arg(long, default_value = "AWSCURRENT")]
+        version_stage: String,
+
+        /// Show the differing values instead of only key names
+        #[arg(long)]
+        reveal: bool,
+
+        /// Override the AWS region
+        #[arg(long)]
+        region: Option<String>,
+
+        /// Use a named AWS credentials profile
+        #[arg(long)]
+        profile: Option<String>,
+
+        /// Maximum retries on throttling or transient AWS errors
+        #[arg(long, default_value_t = aws_client::DEFAULT_MAX_RETRIES)]
+        max_retries: u32,
+    },
+    /// Upload a local env or JSON file as a new version of an existing secret
+    Put {
+        /// Name or ARN of the secret to update
+        secret_name: String,
+
+        /// Local file to upload (KEY=value or JSON); stored as a JSON object
+        #[arg(short, long)]
+        file: String,
+
+        /// Overlay the local keys on the current secret, keeping keys only the secret has,
+        /// instead of replacing it entirely
+        #[arg(long)]
+        merge_remote: bool,
+
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Override the AWS region
+        #[arg(long)]
+        region: Option<String>,
+
+        /// Use a named AWS credentials profile
+        #[arg(long)]
+        profile: Option<String>,
+
+        /// Maximum retries on throttling or transient AWS errors
+        #[arg(long, default_value_t = aws_client::DEFAULT_MAX_RETRIES)]
+        max_retries: u32,
+    },
+    /// Keep an output file in sync with a secret, rewriting it whenever the
+    /// AWSCURRENT version changes (e.g. after rotation). Stop with Ctrl-C.
+    Watch {
+        /// Name or ARN of the secret to watch
+        secret_name: String,
+
+        /// Output format (stdout, json, env, yaml, csv, tfvars, ini, compose-env, direnv, keys).
+        /// Default: inferred from the --file extension, else env
+        #[arg(short, long, value_enum, env = "SM2ENV_OUTPUT")]
+        output: Option<OutputFormat>,
+
+        /// File path to write the output to
+        #[arg(short, long)]
+        file: Option<String>,
+
+        /// Seconds between version checks
+        #[arg(long, value_name = "SECONDS", default_value_t = 60,
+              value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+
         /// Override the AWS region
         #[arg(long)]
         region: Option<String>,
@@ -102,6 +733,10 @@ enum Commands {
         /// Use a named AWS credentials profile
         #[arg(long)]
         profile: Option<String>,
+
+        /// Maximum retries on throttling or transient AWS errors
+        #[arg(long, default_value_t = aws_client::DEFAULT_MAX_RETRIES)]
+        max_retries: u32,
     },
     /// Generate shell completions
     Completions {
@@ -110,82 +745,566 @@ enum Commands {
     },
 }
 
-#[derive(ValueEnum, Clone, Debug)]
-pub enum OutputFormat {
-    Stdout,
+impl Cli {
+    fn profile_files(&self) -> aws_client::ProfileFilePaths<'_> {
+        aws_client::ProfileFilePaths {
+            config: self.aws_config_file.as_deref(),
+            credentials: self.aws_credentials_file.as_deref(),
+        }
+    }
+}
+
+/// Sort key for `list`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum SortBy {
+    Name,
+    Created,
+    Changed,
+}
+
+/// How `get --merge`/`--merge-into` handles a key set by more than one secret.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OnConflict {
+    /// Print a warning and keep the last value
+    Warn,
+    /// Fail without writing anything
+    Error,
+}
+
+/// How `main` reports a fatal error.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ErrorFormat {
+    /// `Error: <message>`
+    Text,
+    /// One JSON object per error, for scripts that parse stderr
     Json,
-    Env,
-    Yaml,
-    Csv,
 }
 
-impl fmt::Display for OutputFormat {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+/// `--log-level` values.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn as_str(self) -> &'static str {
         match self {
-            OutputFormat::Stdout => write!(f, "stdout"),
-            OutputFormat::Json => write!(f, "json"),
-            OutputFormat::Env => write!(f, "env"),
-            OutputFormat::Yaml => write!(f, "yaml"),
-            OutputFormat::Csv => write!(f, "csv"),
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
         }
     }
 }
 
+/// Output format for commands that print metadata rather than secret values.
+#[derive(ValueEnum, Clone, Debug)]
+pub enum MetadataFormat {
+    Text,
+    Json,
+}
+
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> std::process::ExitCode {
     let cli = Cli::parse();
+    ui::set_quiet(cli.quiet);
+    ui::init_color(cli.no_color);
+    ui::init_logging(&ui::log_filter(
+        cli.log_level.map(LogLevel::as_str),
+        cli.verbose,
+        cli.quiet,
+        std::env::var("RUST_LOG").ok(),
+    ));
+
+    match with_timeout(cli.timeout, run(&cli)).await {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(e) => {
+            let code = e.downcast_ref::<SmError>().map_or(1, SmError::exit_code);
+            match cli.error_format {
+                ErrorFormat::Text => ui::print_error(e),
+                ErrorFormat::Json => eprintln!("{}", error_json(e.as_ref())),
+            }
+            std::process::ExitCode::from(code)
+        }
+    }
+}
+
+/// `--error-format json` output: `{"error": ..., "code": ..., "secret": ...}`, with
+/// `secret` only present when the error concerns a known secret.
+fn error_json(e: &(dyn std::error::Error + 'static)) -> Value {
+    let (code, secret) = match e.downcast_ref::<SmError>() {
+        Some(sm) => (sm.code(), sm.secret()),
+        None if e.is::<io::Error>() => ("IoError", None),
+        None => ("Error", None),
+    };
+    let mut doc = Map::new();
+    doc.insert("error".to_string(), Value::String(e.to_string()));
+    doc.insert("code".to_string(), Value::String(code.to_string()));
+    if let Some(secret) = secret {
+        doc.insert("secret".to_string(), Value::String(secret.to_string()));
+    }
+    Value::Object(doc)
+}
+
+/// The `--output` formats given, or else the one implied by the `--file`
+/// extension, or else env.
+fn resolve_output_formats(output: &[OutputFormat], file: Option<&str>) -> Vec<OutputFormat> {
+    if !output.is_empty() {
+        return output.to_vec();
+    }
+    let inferred = file.and_then(|f| OutputFormat::from_path(std::path::Path::new(f)));
+    vec![inferred.unwrap_or(OutputFormat::Env)]
+}
+
+/// Await `fut`, failing with [`SmError::TimeoutError`] if it runs longer than `secs`.
+async fn with_timeout<T>(
+    secs: Option<u64>,
+    fut: impl std::future::Future<Output = Result<T, Box<dyn std::error::Error>>>,
+) -> Result<T, Box<dyn std::error::Error>> {
+    match secs {
+        Some(secs) => tokio::time::timeout(std::time::Duration::from_secs(secs), fut)
+            .await
+            .map_err(|_| SmError::TimeoutError(secs))?,
+        None => fut.await,
+    }
+}
 
+async fn run(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
     match &cli.command {
         Some(Commands::Get {
             secret_names,
+            stdin,
+            all,
+            batch_file,
+            interactive,
+            filter,
             output,
             file,
+            output_dir,
+            secure_dir,
             region,
             profile,
             version_stage,
             prefix,
             keys,
+            include,
+            exclude,
             dry_run,
             append,
             merge,
+            merge_into,
+            compare_stages,
+            on_conflict,
+            array_key,
+            key_name,
+            template,
+            interpolate,
+            decode_base64,
+            detect_type,
+            allow_missing,
+            reveal,
+            max_retries,
+            select,
+            cache_ttl,
+            no_cache,
+            refresh_cache,
+            strict,
+            compose_map,
+            concat,
+            env_suffix,
+            clipboard,
+            header,
+            line_ending,
+            bool_style,
+            array_sep,
+            key_case,
+            json_compact,
+            wrap,
+            delimiter,
+            no_header,
+            align,
+            export_prefix,
+            trim_whitespace,
+            no_trim,
+            escape_dollar,
+            url_encode,
+            yaml_doc_start,
+            yaml_flow,
+            sort_keys,
+            keep_comments,
+            max_value_length,
+            uppercase_keys,
+            report_keys,
+            with_kms_info,
+            fifo_timeout,
+            schema,
+            example,
+            max_concurrency,
+            keyring,
+            to_ssm,
         }) => {
             // Load config file, CLI flags take precedence
             let cfg = config::load_config()?;
-            let effective_region = region.as_deref().or(cfg.region.as_deref());
             let effective_profile = profile.as_deref().or(cfg.profile.as_deref());
 
-            let client = aws_client::build_client(effective_region, effective_profile).await;
-
-            get_secret(
-                &client,
-                &GetOptions {
-                    secret_names,
-                    output_format: output,
-                    file: file.as_deref(),
-                    version_stage,
-                    prefix: prefix.as_deref(),
-                    keys: keys.as_deref(),
-                    dry_run: *dry_run,
-                    append: *append,
-                    merge: *merge,
-                },
+            let (mut client, default_region) = build_client_with_region(
+                region.as_deref(),
+                cfg.region.as_deref(),
+                effective_profile,
+                *max_retries,
+                cli.profile_files(),
             )
-            .await?;
+            .await;
+
+            // --to-ssm writes to the configured region, never to an ARN's
+            let ssm_client = match to_ssm {
+                Some(_) => Some(
+                    build_ssm_client(region.as_deref(), cfg.region.as_deref(), effective_profile, *max_retries, cli.profile_files())
+                        .await,
+                ),
+                None => None,
+            };
+
+            let validator = schema
+                .as_deref()
+                .map(|path| sm2env::schema::load(std::path::Path::new(path)))
+                .transpose()?;
+
+            let secret_names = if *interactive {
+                let name_filter = filter.as_deref().map(|f| NameFilter::new(f, false, false)).transpose()?;
+                let entries = fetch_secret_entries(&client, name_filter.as_ref(), &[]).await?;
+                vec![pick_secret(entries)?]
+            } else {
+                resolve_secret_names(secret_names, *stdin, io::stdin().lock())?
+            };
+            let batch_names = batch_file
+                .as_deref()
+                .map(|path| std::fs::read_to_string(path).map(|list| parse_name_list(&list)))
+                .transpose()?;
+
+            // An ARN names its region, which beats the configured default; --region beats both
+            let mut client_region = default_region.clone();
+            if region.is_none() {
+                let names = batch_names.as_deref().unwrap_or(&secret_names);
+                let needed = aws_client::common_region(names, default_region.as_deref())?;
+                if needed.is_some() && needed != default_region.as_deref() {
+                    client = build_client(needed, None, effective_profile, *max_retries, cli.profile_files()).await;
+                    client_region = needed.map(str::to_string);
+                }
+            }
+
+            let secret_cache = match cache_ttl.or(cfg.cache_ttl) {
+                ttl if *refresh_cache => {
+                    let ttl = std::time::Duration::from_secs(ttl.unwrap_or(0));
+                    Some(cache::Cache::in_default_dir(ttl).refreshing())
+                }
+                Some(ttl) if !*no_cache => {
+                    Some(cache::Cache::in_default_dir(std::time::Duration::from_secs(ttl)))
+                }
+                _ => None,
+            };
+            // Entries are per region and profile (AWS_PROFILE when no profile is given)
+            let cache_profile = effective_profile.map(str::to_string).or_else(|| std::env::var("AWS_PROFILE").ok());
+            let secret_cache =
+                secret_cache.map(|c| c.scoped(client_region.as_deref(), cache_profile.as_deref()));
+
+            let output_formats = resolve_output_formats(output, file.as_deref());
+            let opts = GetOptions {
+                secret_names: &secret_names,
+                output_formats: &output_formats,
+                file: file.as_deref(),
+                output_dir: output_dir.as_deref(),
+                secure_dir: *secure_dir,
+                version_stage,
+                prefix: prefix.as_deref(),
+                keys: keys.as_deref(),
+                include,
+                exclude,
+                dry_run: *dry_run,
+                append: *append,
+                merge: *merge,
+                on_conflict: *on_conflict,
+                array_key,
+                key_name: key_name.as_deref(),
+                template: template.as_deref(),
+                interpolate: *interpolate,
+                decode_base64: *decode_base64,
+                detect_type: *detect_type,
+                allow_missing: *allow_missing,
+                reveal: *reveal,
+                select: select.as_deref(),
+                cache: secret_cache.as_ref(),
+                strict: *strict,
+                name_stem: None,
+                convert: converters::ConvertOptions {
+                    compose_map: *compose_map,
+                    line_ending: *line_ending,
+                    json_compact: *json_compact,
+                    csv_delimiter: *delimiter,
+                    csv_no_header: *no_header,
+                    env_align: *align,
+                    env_export_prefix: *export_prefix,
+                    env_escape_dollar: *escape_dollar,
+                    env_url_encode: *url_encode,
+                    bool_style: *bool_style,
+                    env_array_sep: *array_sep,
+                    key_case: *key_case,
+                    yaml_doc_start: *yaml_doc_start,
+                    yaml_flow: *yaml_flow,
+                    example: *example,
+                },
+                concat: *concat,
+                env_suffix: env_suffix.as_deref(),
+                clipboard: *clipboard,
+                header: *header,
+                max_concurrency: *max_concurrency,
+                sort_keys: *sort_keys,
+                keep_comments: *keep_comments,
+                max_value_length: *max_value_length,
+                uppercase_keys: *uppercase_keys,
+                report_keys: *report_keys,
+                with_kms_info: *with_kms_info,
+                fifo_timeout: std::time::Duration::from_secs(*fifo_timeout),
+                schema: validator.as_ref(),
+                keyring: keyring.as_deref(),
+                to_ssm: ssm_client
+                    .as_ref()
+                    .zip(to_ssm.as_deref())
+                    .map(|(client, prefix)| SsmTarget { client, prefix }),
+                trim: match (*trim_whitespace, *no_trim) {
+                    (true, _) => TrimMode::All,
+                    (_, true) => TrimMode::None,
+                    _ => TrimMode::KeyValueOnly,
+                },
+                wrap: *wrap,
+            };
+
+            if *compare_stages {
+                let [secret_name] = secret_names.as_slice() else {
+                    return Err(SmError::FormatError("--compare-stages takes exactly one secret name.".to_string()).into());
+                };
+                compare_version_stages(&client, secret_name, *reveal).await?;
+            } else if let Some(path) = merge_into {
+                let names = if *all {
+                    all_secret_names(&client, filter.as_deref()).await?
+                } else if let Some(names) = batch_names {
+                    names
+                } else {
+                    secret_names.clone()
+                };
+                if names.is_empty() {
+                    ui::info!("No secrets found.");
+                    return Ok(());
+                }
+                let opts = GetOptions {
+                    secret_names: &names,
+                    output_formats: &[OutputFormat::Json],
+                    file: Some(path),
+                    merge: true,
+                    on_conflict: Some(on_conflict.unwrap_or(OnConflict::Warn)),
+                    ..opts
+                };
+                get_secret(&client, &opts).await?;
+            } else if *all {
+                get_all_secrets(&client, filter.as_deref(), &opts).await?;
+            } else if let Some(names) = &batch_names {
+                get_each_secret(&client, names, &opts).await?;
+            } else {
+                get_secret(&client, &opts).await?;
+            }
         }
-        Some(Commands::List { filter, region, profile }) => {
+        Some(Commands::List {
+            filter,
+            case_sensitive,
+            ignore_case: _,
+            glob,
+            sort_by,
+            no_sort,
+            tags,
+            since,
+            region,
+            all_regions,
+            interactive,
+            count,
+            start_token,
+            paginate,
+            no_pager,
+            profile,
+            max_retries,
+        }) => {
+            let name_filter = filter
+                .as_deref()
+                .map(|f| NameFilter::new(f, *glob, *case_sensitive))
+                .transpose()?;
             let cfg = config::load_config()?;
-            let effective_region = region.as_deref().or(cfg.region.as_deref());
+            let effective_region = region.as_deref();
             let effective_profile = profile.as_deref().or(cfg.profile.as_deref());
-            let client = aws_client::build_client(effective_region, effective_profile).await;
-            list_secrets(&client, filter.as_deref()).await?;
-        }
-        Some(Commands::Completions { shell }) => {
-            let mut cmd = Cli::command();
-            generate(*shell, &mut cmd, "sm2env", &mut io::stdout());
-        }
-        None => {
-            let mut cmd = Cli::command();
-            cmd.print_help()?;
+            let sort_by = if *no_sort { None } else { Some(*sort_by) };
+            let cutoff = since.map(|d| changed_cutoff(std::time::SystemTime::now(), d));
+            let paginate = *paginate && !*no_pager;
+            if *all_regions || region.as_deref() == Some("all") {
+                if start_token.is_some() {
+                    return Err(SmError::FormatError(
+                        "--start-token cannot be used when listing all regions.".to_string(),
+                    )
+                    .into());
+                }
+                let clients = RegionClients {
+                    profile: effective_profile,
+                    max_retries: *max_retries,
+                    profile_files: cli.profile_files(),
+                };
+                let listing =
+                    list_secrets_all_regions(&clients, name_filter.as_ref(), tags, cutoff, sort_by, *count).await?;
+                ui::print_paged(&listing, paginate)?;
+            } else {
+                let client =
+                    build_client(effective_region, cfg.region.as_deref(), effective_profile, *max_retries, cli.profile_files())
+                        .await;
+                if *interactive {
+                    let mut entries =
+                        fetch_secret_entries_from(&client, name_filter.as_ref(), tags, start_token.as_deref())
+                            .await?;
+                    if let Some(cutoff) = cutoff {
+                        entries.retain(|e| changed_since(e, cutoff));
+                    }
+                    println!("{}", pick_secret(entries)?);
+                } else {
+                    let start_token = start_token.as_deref();
+                    let listing =
+                        list_secrets(&client, name_filter.as_ref(), tags, cutoff, sort_by, *count, start_token)
+                            .await?;
+                    ui::print_paged(&listing, paginate)?;
+                }
+            }
+        }
+        Some(Commands::Describe {
+            secret_name,
+            output,
+            region,
+            profile,
+            max_retries,
+        }) => {
+            let cfg = config::load_config()?;
+            let effective_region = region
+                .as_deref()
+                .or(aws_client::region_from_arn(secret_name));
+            let effective_profile = profile.as_deref().or(cfg.profile.as_deref());
+            let client =
+                build_client(effective_region, cfg.region.as_deref(), effective_profile, *max_retries, cli.profile_files()).await;
+            describe_secret(&client, secret_name, output).await?;
+        }
+        Some(Commands::Rotate {
+            secret_name,
+            rotation_lambda_arn,
+            immediate,
+            region,
+            profile,
+            max_retries,
+        }) => {
+            let cfg = config::load_config()?;
+            let effective_region = region
+                .as_deref()
+                .or(aws_client::region_from_arn(secret_name));
+            let effective_profile = profile.as_deref().or(cfg.profile.as_deref());
+            let client =
+                build_client(effective_region, cfg.region.as_deref(), effective_profile, *max_retries, cli.profile_files()).await;
+            rotate_secret(&client, secret_name, rotation_lambda_arn.as_deref(), *immediate).await?;
+        }
+        Some(Commands::Delete {
+            secret_name,
+            force,
+            recovery_days,
+            yes,
+            region,
+            profile,
+            max_retries,
+        }) => {
+            let cfg = config::load_config()?;
+            let effective_region = region
+                .as_deref()
+                .or(aws_client::region_from_arn(secret_name));
+            let effective_profile = profile.as_deref().or(cfg.profile.as_deref());
+            let client =
+                build_client(effective_region, cfg.region.as_deref(), effective_profile, *max_retries, cli.profile_files()).await;
+            delete_secret(&client, secret_name, *force, *recovery_days, *yes).await?;
+        }
+        Some(Commands::Diff {
+            secret_name,
+            file,
+            version_stage,
+            reveal,
+            region,
+            profile,
+            max_retries,
+        }) => {
+            let cfg = config::load_config()?;
+            let effective_region = region
+                .as_deref()
+                .or(aws_client::region_from_arn(secret_name));
+            let effective_profile = profile.as_deref().or(cfg.profile.as_deref());
+            let local = secret_to_map(detect_secret_format(&std::fs::read_to_string(file)?));
+            let client =
+                build_client(effective_region, cfg.region.as_deref(), effective_profile, *max_retries, cli.profile_files()).await;
+            diff_secret(&client, secret_name, version_stage, &local, *reveal).await?;
+        }
+        Some(Commands::Put {
+            secret_name,
+            file,
+            merge_remote,
+            yes,
+            region,
+            profile,
+            max_retries,
+        }) => {
+            let cfg = config::load_config()?;
+            let effective_region = region
+                .as_deref()
+                .or(aws_client::region_from_arn(secret_name));
+            let effective_profile = profile.as_deref().or(cfg.profile.as_deref());
+            let local = secret_to_map(detect_secret_format(&std::fs::read_to_string(file)?));
+            let client =
+                build_client(effective_region, cfg.region.as_deref(), effective_profile, *max_retries, cli.profile_files()).await;
+            put_secret(&client, secret_name, local, *merge_remote, *yes).await?;
+        }
+        Some(Commands::Watch {
+            secret_name,
+            output,
+            file,
+            interval,
+            region,
+            profile,
+            max_retries,
+        }) => {
+            let cfg = config::load_config()?;
+            let effective_region = region
+                .as_deref()
+                .or(aws_client::region_from_arn(secret_name));
+            let effective_profile = profile.as_deref().or(cfg.profile.as_deref());
+            let client =
+                build_client(effective_region, cfg.region.as_deref(), effective_profile, *max_retries, cli.profile_files()).await;
+            let output_formats = resolve_output_formats(output.as_slice(), file.as_deref());
+            let opts = GetOptions {
+                secret_names: std::slice::from_ref(secret_name),
+                output_formats: &output_formats,
+                file: file.as_deref(),
+                // Each check must see the live version, never a cached one
+                cache: None,
+                ..GetOptions::default()
+            };
+            watch_secret(&client, &opts, std::time::Duration::from_secs(*interval)).await?;
+        }
+        Some(Commands::Completions { shell }) => {
+            write_completions(*shell, &mut io::stdout());
+        }
+        None => {
+            let mut cmd = Cli::command();
+            cmd.print_help()?;
         }
     }
 
@@ -197,14 +1316,31 @@ async fn get_secret(
     opts: &GetOptions<'_>,
 ) -> Result<(), SmError> {
     let secret_names = opts.secret_names;
-    let output_format = opts.output_format;
+    let output_formats = opts.output_formats;
     let file = opts.file;
+    let output_dir = opts.output_dir;
     let version_stage = opts.version_stage;
     let prefix = opts.prefix;
     let keys = opts.keys;
     let dry_run = opts.dry_run;
     let append = opts.append;
     let merge = opts.merge;
+    let array_key = opts.array_key;
+    let template = opts.template;
+    let allow_missing = opts.allow_missing;
+    let reveal = opts.reveal;
+    let select = opts.select;
+    let secret_cache = opts.cache;
+    let strict = opts.strict;
+    let name_stem = opts.name_stem;
+    let concat = opts.concat;
+    let target = output::OutputTarget {
+        file,
+        output_dir,
+        name_stem,
+        env_suffix: opts.env_suffix,
+    };
+    let convert_opts = opts.convert;
 
     if secret_names.len() > 1 && !merge {
         return Err(SmError::FormatError(
@@ -213,26 +1349,108 @@ async fn get_secret(
         ));
     }
 
+    if concat
+        && (template.is_some()
+            || output_formats
+                .iter()
+                .any(|f| !matches!(f, OutputFormat::Env | OutputFormat::Stdout)))
+    {
+        return Err(SmError::FormatError(
+            "--concat only supports env and stdout output formats.".to_string(),
+        ));
+    }
+
+    let prints_only = template.is_some() || output_formats.iter().all(|f| target.resolve(Some(f)).is_none());
+    if opts.clipboard && select.is_none() && !prints_only {
+        return Err(SmError::FormatError(
+            "--clipboard needs --select, --template, or --output stdout.".to_string(),
+        ));
+    }
+
+    if output_formats.len() > 1 && file.is_some() {
+        return Err(SmError::FormatError(
+            "Multiple --output formats cannot share one --file. Use --output-dir instead.".to_string(),
+        ));
+    }
+
     // Fetch and merge all secrets
     let mut merged_map: Map<String, Value> = Map::new();
+    // Raw elements of a single JSON array secret, rendered as-is for json/yaml/stdout
+    let mut array_items: Option<Vec<Value>> = None;
+    // Original text of a single plain-text secret, stored as-is by --keyring
+    let mut plain_text: Option<String> = None;
+    // Line structure of a single plain-text secret, kept in env output by --keep-comments
+    let mut env_layout: Option<Vec<detect::EnvLine>> = None;
+    // Version of a single fetched secret, reported by --wrap
+    let mut version_id: Option<String> = None;
 
-    for secret_name in secret_names {
-        let response = client
-            .get_secret_value()
-            .secret_id(secret_name)
-            .version_stage(version_stage)
-            .send()
-            .await
-            .map_err(|e| SmError::AwsError(e.to_string()))?;
-
-        let map = if let Some(secret_string) = response.secret_string {
-            let fmt = detect_secret_format(&secret_string);
-            secret_to_map(fmt)
-        } else if let Some(secret_binary) = response.secret_binary {
-            let base64_str =
-                base64::engine::general_purpose::STANDARD.encode(secret_binary.as_ref());
+    let fetched = run_bounded(secret_names, opts.max_concurrency, |name| {
+        fetch_secret(client, name, version_stage, secret_cache)
+    })
+    .await;
+
+    for (secret_name, secret) in secret_names.iter().zip(fetched) {
+        let mut secret = secret?;
+        if opts.decode_base64 {
+            secret = decode_base64_secret(secret);
+        }
+        if secret_names.len() == 1 {
+            version_id = secret.version_id.take();
+        }
+
+        let mut map = if let Some(secret_string) = secret.secret_string {
+            let format = detect_secret_format(&secret_string);
+            if let Some(validator) = opts.schema {
+                sm2env::schema::validate(validator, &format, secret_name)?;
+            }
+            match format {
+                SecretFormat::Array(mut items) => {
+                    if opts.trim == TrimMode::All {
+                        items.iter_mut().for_each(detect::trim_value);
+                    }
+                    let map = array_to_map(&items, array_key);
+                    if secret_names.len() == 1 {
+                        array_items = Some(items);
+                    }
+                    map
+                }
+                SecretFormat::PlainText(text) => {
+                    if opts.keyring.is_some() && secret_names.len() == 1 {
+                        plain_text = Some(text.clone());
+                    }
+                    if opts.keep_comments && secret_names.len() == 1 {
+                        env_layout = Some(detect::env_layout(&text));
+                    }
+                    let duplicates = detect::duplicate_env_keys(&text);
+                    if !duplicates.is_empty() {
+                        let message = format!(
+                            "duplicate keys in secret '{}' (last value wins): {}",
+                            secret_name,
+                            duplicates.join(", ")
+                        );
+                        if strict {
+                            return Err(SmError::FormatError(message));
+                        }
+                        tracing::warn!("{}", message);
+                    }
+                    let key = opts.key_name.unwrap_or(detect::DEFAULT_PLAIN_KEY);
+                    detect::plain_text_to_map(text, key, opts.trim)
+                }
+                fmt => secret_to_map(fmt),
+            }
+        } else if let Some(base64_str) = secret.secret_binary {
+            if opts.schema.is_some() {
+                return Err(SmError::SchemaError(format!(
+                    "secret '{}' is binary, so it cannot match the schema",
+                    secret_name
+                )));
+            }
+            if opts.detect_type {
+                report_binary_type(secret_name, &base64_str);
+            }
             let mut map = Map::new();
-            map.insert("binary_data".to_string(), Value::String(base64_str));
+            let key = opts.key_name.unwrap_or(detect::DEFAULT_BINARY_KEY);
+            map.insert(key.to_string(), Value::String(base64_str));
             map
         } else {
             return Err(SmError::FormatError(
@@ -240,44 +1458,97 @@ async fn get_secret(
             ));
         };
 
+        if opts.trim == TrimMode::All {
+            map.values_mut().for_each(detect::trim_value);
+        }
+        if let Some(on_conflict) = opts.on_conflict {
+            check_conflicts(&merged_map, &map, secret_name, on_conflict)?;
+        }
         merged_map.extend(map);
     }
 
-    // Warn/error on empty secret
-    if merged_map.is_empty() {
-        return Err(SmError::FormatError(
-            "Secret contains no key-value pairs (empty object {}).".to_string(),
-        ));
+    ensure_has_keys(&merged_map, secret_names, opts.strict)?;
+
+    if opts.with_kms_info {
+        report_kms_keys(client, secret_names, opts.max_concurrency).await?;
+    }
+
+    // Resolve ${KEY} references between values before any key is filtered or renamed
+    if opts.interpolate {
+        merged_map = template::interpolate(&merged_map, allow_missing)?;
+    }
+
+    // Handle --select: print a single raw value and skip format handling
+    if let Some(path) = select {
+        let value = select_value(&merged_map, path)
+            .ok_or_else(|| SmError::FormatError(format!("Key '{}' not found in secret", path)))?;
+        if opts.clipboard {
+            clipboard::copy(&sm2env::format_value(value))?;
+            ui::info!("Copied to clipboard");
+        } else {
+            println!("{}", sm2env::format_value(value));
+        }
+        return Ok(());
     }
 
     // Apply --keys filter
     if let Some(keys_str) = keys {
         let requested: Vec<&str> = keys_str.split(',').map(|k| k.trim()).collect();
-        let mut filtered = Map::new();
-        for key in &requested {
-            if let Some(val) = merged_map.remove(*key) {
-                filtered.insert(key.to_string(), val);
-            } else {
-                eprintln!("Warning: key '{}' not found in secret", key);
-            }
+        let (filtered, missing) = sm2env::filter_keys(merged_map, &requested);
+        for key in missing {
+            tracing::warn!("key '{}' not found in secret", key);
         }
         merged_map = filtered;
     }
 
+    // Apply --include/--exclude
+    let key_globs = !opts.include.is_empty() || !opts.exclude.is_empty();
+    if key_globs {
+        merged_map = sm2env::include_exclude_keys(merged_map, opts.include, opts.exclude)?;
+    }
+
     // Apply --prefix
     if let Some(pfx) = prefix {
-        let prefixed: Map<String, Value> = merged_map
-            .into_iter()
-            .map(|(k, v)| (format!("{}{}", pfx, k), v))
-            .collect();
-        merged_map = prefixed;
+        merged_map = sm2env::prefix_keys(merged_map, pfx);
+    }
+
+    // Apply --uppercase-keys, keeping what changed for --report-keys
+    let mut key_report = None;
+    if opts.uppercase_keys {
+        let (normalized, report) = converters::env::normalize_env_keys(merged_map);
+        merged_map = normalized;
+        key_report = opts.report_keys.then_some(report);
+    }
+
+    if let Some(limit) = opts.max_value_length {
+        check_value_lengths(&merged_map, limit)?;
+    }
+
+    // Handle --keyring: store in the OS keychain instead of writing output
+    if let Some(service) = opts.keyring {
+        let untouched =
+            keys.is_none() && !key_globs && prefix.is_none() && !opts.uppercase_keys && !opts.interpolate;
+        let payload = keyring_payload(plain_text.filter(|_| untouched), &merged_map)?;
+        let account = secret_names.join(",");
+        keychain::store(service, &account, &payload)?;
+        ui::info!("Stored {} in the keyring under service '{}'", ui::name(&account), service);
+        return Ok(());
+    }
+
+    // Handle --to-ssm: write parameters instead of output
+    if let Some(ssm) = opts.to_ssm {
+        let written = write_to_ssm(&ssm, &merged_map).await?;
+        ui::info!("Wrote {} parameter(s) under '{}'", ui::count(written), ssm.prefix);
+        return Ok(());
     }
 
     // Handle --append: merge into existing .env file
     let effective_map = if append {
-        let target = file.unwrap_or(".env");
-        let mut existing: Map<String, Value> = if std::path::Path::new(target).exists() {
-            let content = std::fs::read_to_string(target)?;
+        let append_path = target
+            .resolve(Some(&OutputFormat::Env))
+            .unwrap_or_else(|| std::path::PathBuf::from(".env"));
+        let mut existing: Map<String, Value> = if append_path.exists() {
+            let content = std::fs::read_to_string(&append_path)?;
             parse_env_vars(&content)
         } else {
             Map::new()
@@ -288,67 +1559,701 @@ async fn get_secret(
         merged_map
     };
 
-    // Convert to output format
-    let content = converters::convert_to_format(&effective_map, output_format)?;
+    // Keys keep the secret's order unless --sort-keys asks for alphabetical order
+    let effective_map = if opts.sort_keys {
+        if let Some(items) = &mut array_items {
+            items.iter_mut().for_each(Value::sort_all_objects);
+        }
+        sm2env::sort_keys(effective_map)
+    } else {
+        effective_map
+    };
 
-    // Determine output destination
-    if dry_run {
-        print!("{}", content);
-        return Ok(());
+    // Validate key names for env-style output
+    let env_output = template.is_none()
+        && output_formats
+            .iter()
+            .any(|f| {
+                matches!(
+                    f,
+                    OutputFormat::Env | OutputFormat::Stdout | OutputFormat::Direnv | OutputFormat::Keys
+                )
+            });
+    if env_output {
+        let invalid = converters::env::invalid_env_keys(&effective_map);
+        if !invalid.is_empty() {
+            let message = format!("invalid environment variable names: {}", invalid.join(", "));
+            if strict {
+                return Err(SmError::FormatError(message));
+            }
+            tracing::warn!("{}", message);
+        }
+        let collisions = convert_opts.key_case.collisions(&effective_map);
+        if !collisions.is_empty() {
+            let pairs: Vec<String> =
+                collisions.iter().map(|(key, first)| format!("{} (same as {})", key, first)).collect();
+            let message = format!("keys collide after --key-case, keeping the first: {}", pairs.join(", "));
+            if strict {
+                return Err(SmError::FormatError(message));
+            }
+            tracing::warn!("{}", message);
+        }
     }
 
-    let output_path: Option<std::path::PathBuf> = if matches!(output_format, OutputFormat::Stdout) && file.is_none() {
-        None
-    } else {
-        let p = file.map(|f| f.to_string()).unwrap_or_else(|| {
-            match output_format {
-                OutputFormat::Json => "secret.json".to_string(),
-                OutputFormat::Yaml => "secret.yaml".to_string(),
-                OutputFormat::Csv => "secret.csv".to_string(),
-                _ => ".env".to_string(),
+    if template.is_none() && output_formats.iter().any(|f| matches!(f, OutputFormat::Tfvars)) {
+        let invalid = converters::tfvars::invalid_keys(&effective_map);
+        if !invalid.is_empty() {
+            let message = format!("keys that are not valid tfvars names are skipped: {}", invalid.join(", "));
+            if strict {
+                return Err(SmError::FormatError(message));
             }
+            tracing::warn!("{}", message);
+        }
+    }
+
+    // Render the template once, or convert to each requested format; an untouched
+    // array secret keeps its shape
+    let header = opts.header.then(|| sm2env::header::Header {
+        secret_names,
+        generated_at: std::time::SystemTime::now(),
+    });
+    let wrap = opts.wrap.then(|| (secret_names.join(","), version_id));
+    let untouched_array = array_items
+        .as_ref()
+        .filter(|_| {
+            keys.is_none()
+                && !key_globs
+                && prefix.is_none()
+                && !opts.uppercase_keys
+                && !append
+                && !opts.interpolate
         });
-        Some(std::path::PathBuf::from(p))
+    let stdout_is_tty = io::stdout().is_terminal();
+    let rendered: Vec<(Option<&OutputFormat>, Rendered)> = if let Some(template_path) = template {
+        let template_content = std::fs::read_to_string(template_path)?;
+        let content = template::render(&template_content, &effective_map, allow_missing)?;
+        vec![(None, Rendered::Text(content))]
+    } else {
+        output_formats
+            .iter()
+            .map(|output_format| {
+                let to_stdout =
+                    !opts.clipboard && (dry_run || target.resolve(Some(output_format)).is_none());
+                let mask = should_mask(output_format, to_stdout, stdout_is_tty, reveal);
+                if let OutputFormat::Json = output_format {
+                    let data = match untouched_array {
+                        Some(items) => Value::Array(items.clone()),
+                        None => Value::Object(effective_map.clone()),
+                    };
+                    let wrap = wrap.as_ref().map(|(name, version)| (name.as_str(), version.as_deref()));
+                    let doc = json_document(data, wrap, header.as_ref());
+                    let content = if to_stdout || opts.clipboard {
+                        Rendered::Text(converters::json::render(&doc, convert_opts.json_compact)?)
+                    } else {
+                        Rendered::Json(doc)
+                    };
+                    return Ok((Some(output_format), content));
+                }
+                let content = if let (OutputFormat::Env, Some(layout)) = (output_format, &env_layout) {
+                    let data = if mask {
+                        std::borrow::Cow::Owned(converters::mask_values(&effective_map))
+                    } else {
+                        std::borrow::Cow::Borrowed(&effective_map)
+                    };
+                    converters::env::convert_with_layout(&data, layout, &convert_opts)
+                } else if mask {
+                    converters::convert_to_format(
+                        &converters::mask_values(&effective_map),
+                        output_format,
+                        &convert_opts,
+                    )?
+                } else {
+                    match untouched_array {
+                        Some(items) => converters::convert_array(items, output_format, array_key, &convert_opts)?,
+                        None => converters::convert_to_format(&effective_map, output_format, &convert_opts)?,
+                    }
+                };
+                let content = match &header {
+                    Some(h) => h.prepend(content, output_format, convert_opts.line_ending),
+                    None => content,
+                };
+                Ok((Some(output_format), Rendered::Text(content)))
+            })
+            .collect::<Result<_, SmError>>()?
     };
 
-    output::write_output(&content, output_path.as_deref())?;
+    // Determine output destination
+    if opts.clipboard {
+        let text: String = rendered.iter().filter_map(|(_, content)| content.as_text()).collect();
+        clipboard::copy(&text)?;
+        ui::info!("Copied to clipboard");
+    } else if dry_run {
+        for (_, content) in &rendered {
+            print!("{}", content.as_text().unwrap_or_default());
+        }
+    } else {
+        write_rendered(&rendered, &target, opts).await?;
+    }
 
-    if let Some(ref p) = output_path {
-        println!("Secret written to: {}", p.display());
+    if let Some(report) = key_report {
+        print_key_report(&secret_names.join(","), &report);
     }
 
     Ok(())
 }
 
-async fn list_secrets(
-    client: &aws_sdk_secretsmanager::Client,
-    filter: Option<&str>,
+/// Print the sniffed file type of a binary secret for `--detect-type`.
+fn report_binary_type(secret_name: &str, base64_str: &str) {
+    let bytes = base64::engine::general_purpose::STANDARD.decode(base64_str).unwrap_or_default();
+    match detect::sniff_binary_type(&bytes) {
+        Some((extension, mime)) => {
+            tracing::info!("Binary secret '{}' looks like {} (.{})", secret_name, mime, extension)
+        }
+        None => tracing::info!("Binary secret '{}' has an unrecognized file type", secret_name),
+    }
+}
+
+/// Report keys in `incoming` that an earlier merged secret already set.
+fn check_conflicts(
+    merged: &Map<String, Value>,
+    incoming: &Map<String, Value>,
+    secret_name: &str,
+    on_conflict: OnConflict,
 ) -> Result<(), SmError> {
-    let mut secrets = Vec::new();
+    let clashes: Vec<&str> = incoming
+        .keys()
+        .filter(|k| merged.contains_key(*k))
+        .map(String::as_str)
+        .collect();
+    if clashes.is_empty() {
+        return Ok(());
+    }
+    let message = format!(
+        "secret '{}' overrides keys from earlier secrets: {}",
+        secret_name,
+        clashes.join(", ")
+    );
+    match on_conflict {
+        OnConflict::Warn => {
+            tracing::warn!("{} (last value wins)", message);
+            Ok(())
+        }
+        OnConflict::Error => Err(SmError::FormatError(message)),
+    }
+}
+
+/// Fail with every key whose value is longer than `limit` bytes (`--max-value-length`).
+fn check_value_lengths(data: &Map<String, Value>, limit: usize) -> Result<(), SmError> {
+    let oversized = sm2env::oversized_values(data, limit);
+    if oversized.is_empty() {
+        return Ok(());
+    }
+    let keys: Vec<String> = oversized
+        .iter()
+        .map(|(key, len)| format!("{} ({} bytes)", key, len))
+        .collect();
+    Err(SmError::FormatError(format!(
+        "values longer than --max-value-length {}: {}",
+        limit,
+        keys.join(", ")
+    )))
+}
+
+/// Write each rendered output to its file, or to stdout.
+async fn write_rendered(
+    rendered: &[(Option<&OutputFormat>, Rendered)],
+    target: &output::OutputTarget<'_>,
+    opts: &GetOptions<'_>,
+) -> Result<(), SmError> {
+    let json_compact = opts.convert.json_compact;
+    match target.output_dir {
+        Some(dir) if opts.secure_dir => output::create_private_dir(std::path::Path::new(dir))?,
+        Some(dir) => std::fs::create_dir_all(dir)?,
+        None => {}
+    }
+
+    for (output_format, content) in rendered {
+        let output_path = target.resolve(*output_format);
+
+        let bytes = match (content, output_path.as_deref()) {
+            // A named pipe is written in place; a pipe has nothing to append to
+            (content, Some(p)) if output::is_fifo(p) => {
+                let text = match content {
+                    Rendered::Text(text) => std::borrow::Cow::Borrowed(text.as_str()),
+                    Rendered::Json(doc) => converters::json::render(doc, json_compact)?.into(),
+                };
+                output::write_fifo(p, text.as_bytes(), opts.fifo_timeout).await?;
+                text.len()
+            }
+            (Rendered::Json(doc), Some(p)) => {
+                output::write_json(p, doc, json_compact)?;
+                std::fs::metadata(p)?.len() as usize
+            }
+            (Rendered::Json(_), None) => unreachable!("JSON for stdout is rendered as text"),
+            (Rendered::Text(text), Some(p)) if opts.concat => {
+                output::append_output(text, p)?;
+                text.len()
+            }
+            (Rendered::Text(text), p) => {
+                output::write_output(text, p)?;
+                text.len()
+            }
+        };
+        tracing::debug!(
+            "Wrote {} bytes to {}",
+            bytes,
+            output_path.as_deref().map_or("stdout".into(), |p| p.display().to_string())
+        );
+
+        if let Some(ref p) = output_path {
+            ui::info!("Secret written to: {}", p.display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Log the `--report-keys` summary of renamed and dropped keys.
+fn print_key_report(secret_name: &str, report: &converters::env::KeyReport) {
+    if report.renamed.is_empty() && report.dropped.is_empty() {
+        tracing::info!("Keys in '{}' were already valid names", secret_name);
+        return;
+    }
+    let changes: Vec<String> = report
+        .renamed
+        .iter()
+        .map(|(from, to)| format!("{} → {}", from, to))
+        .chain(report.dropped.iter().map(|key| format!("{} (dropped)", key)))
+        .collect();
+    tracing::info!("Key changes in '{}': {}", secret_name, changes.join(", "));
+}
+
+/// Output rendered for one format. JSON bound for a file stays a value and is
+/// streamed to disk, so a large secret is never buffered as one big string.
+enum Rendered {
+    Text(String),
+    Json(Value),
+}
+
+impl Rendered {
+    /// The rendered text; `None` for JSON that is streamed to a file instead.
+    fn as_text(&self) -> Option<&str> {
+        match self {
+            Rendered::Text(text) => Some(text),
+            Rendered::Json(_) => None,
+        }
+    }
+}
+
+/// The document written for `--output json`: `data` nested by `--wrap` as
+/// `(name, version)`, plus the `--header` provenance key when the top level is an object.
+fn json_document(data: Value, wrap: Option<(&str, Option<&str>)>, header: Option<&sm2env::header::Header>) -> Value {
+    let doc = match wrap {
+        Some((name, version)) => Value::Object(sm2env::wrap_json(data, name, version)),
+        None => data,
+    };
+    match (header, doc) {
+        (Some(h), Value::Object(mut doc)) => {
+            doc.insert(sm2env::header::META_KEY.to_string(), h.meta());
+            Value::Object(doc)
+        }
+        (_, doc) => doc,
+    }
+}
+
+/// An empty secret (`{}`) is usually a misconfiguration: warn and write the empty
+/// output, or fail under `--strict`.
+fn ensure_has_keys(data: &Map<String, Value>, secret_names: &[String], strict: bool) -> Result<(), SmError> {
+    if data.is_empty() {
+        let message = format!("secret {} contains no keys (empty object {{}})", secret_names.join(", "));
+        if strict {
+            return Err(SmError::FormatError(message));
+        }
+        tracing::warn!("{}", message);
+    }
+    Ok(())
+}
+
+/// Write the completion script for `shell`.
+fn write_completions(shell: Shell, out: &mut impl io::Write) {
+    let mut cmd = Cli::command();
+    generate(shell, &mut cmd, "sm2env", out);
+}
+
+/// With `--decode-base64`, a string secret that looks like base64 is decoded.
+/// UTF-8 content is then handled like any string secret (JSON, KEY=value, or plain
+/// text); anything else is treated as a binary secret.
+fn decode_base64_secret(secret: cache::SecretValue) -> cache::SecretValue {
+    let Some(text) = secret.secret_string.as_deref().map(str::trim) else {
+        return secret;
+    };
+    if !detect::looks_like_base64(text) {
+        return secret;
+    }
+    let Ok(bytes) = base64::engine::general_purpose::STANDARD.decode(text) else {
+        return secret;
+    };
+    match String::from_utf8(bytes) {
+        Ok(decoded) => cache::SecretValue {
+            secret_string: Some(decoded),
+            secret_binary: None,
+            version_id: secret.version_id.clone(),
+        },
+        Err(_) => cache::SecretValue {
+            secret_string: None,
+            secret_binary: Some(text.to_string()),
+            version_id: secret.version_id.clone(),
+        },
+    }
+}
+
+/// Load the AWS config and build a client, reporting the load time with `--verbose`.
+async fn build_client(
+    region: Option<&str>,
+    fallback_region: Option<&str>,
+    profile: Option<&str>,
+    max_retries: u32,
+    profile_files: aws_client::ProfileFilePaths<'_>,
+) -> aws_sdk_secretsmanager::Client {
+    build_client_with_region(region, fallback_region, profile, max_retries, profile_files).await.0
+}
+
+/// [`build_client`], also returning the region the client resolved to.
+async fn build_client_with_region(
+    region: Option<&str>,
+    fallback_region: Option<&str>,
+    profile: Option<&str>,
+    max_retries: u32,
+    profile_files: aws_client::ProfileFilePaths<'_>,
+) -> (aws_sdk_secretsmanager::Client, Option<String>) {
+    let started = std::time::Instant::now();
+    let (client, resolved) =
+        aws_client::build_client(region, fallback_region, profile, max_retries, profile_files).await;
+    tracing::debug!("AWS config load took {:?}", started.elapsed());
+    match &resolved {
+        Some((region, source)) => tracing::debug!("Using region {} (from {})", region, source),
+        None => tracing::debug!("No region configured"),
+    }
+    (client, resolved.map(|(region, _)| region))
+}
+
+/// [`build_client`] for SSM Parameter Store, used by `--to-ssm`.
+async fn build_ssm_client(
+    region: Option<&str>,
+    fallback_region: Option<&str>,
+    profile: Option<&str>,
+    max_retries: u32,
+    profile_files: aws_client::ProfileFilePaths<'_>,
+) -> aws_sdk_ssm::Client {
+    let (config, _) =
+        aws_client::load_sdk_config(region, fallback_region, profile, max_retries, profile_files).await;
+    aws_sdk_ssm::Client::new(&config)
+}
+
+/// Ask the user to pick one of `entries` by name with the `--interactive` picker.
+fn pick_secret(entries: Vec<SecretListEntry>) -> Result<String, SmError> {
+    let mut names: Vec<String> = entries.into_iter().filter_map(|s| s.name).collect();
+    names.sort();
+    picker::pick(&names)
+}
+
+/// Replace a `-` secret name (or all names with `--stdin`) with a name read from stdin.
+/// Stdin is only read when needed.
+fn resolve_secret_names(
+    names: &[String],
+    from_stdin: bool,
+    reader: impl io::BufRead,
+) -> Result<Vec<String>, SmError> {
+    if !from_stdin && !names.iter().any(|n| n == "-") {
+        return Ok(names.to_vec());
+    }
+    let name = read_secret_name(reader)?;
+    if from_stdin {
+        return Ok(vec![name]);
+    }
+    Ok(names
+        .iter()
+        .map(|n| if n == "-" { name.clone() } else { n.clone() })
+        .collect())
+}
+
+/// Read the first non-empty line as a secret name.
+fn read_secret_name(reader: impl io::BufRead) -> Result<String, SmError> {
+    for line in reader.lines() {
+        let line = line?;
+        let name = line.trim();
+        if !name.is_empty() {
+            return Ok(name.to_string());
+        }
+    }
+    Err(SmError::ParseError("No secret name provided on stdin".to_string()))
+}
+
+/// Fetch a secret's raw content, serving it from the cache when a fresh entry exists.
+#[tracing::instrument(level = "debug", skip_all, fields(secret = %secret_name, stage = %version_stage))]
+async fn fetch_secret(
+    client: &aws_sdk_secretsmanager::Client,
+    secret_name: &str,
+    version_stage: &str,
+    secret_cache: Option<&cache::Cache>,
+) -> Result<cache::SecretValue, SmError> {
+    let now = std::time::SystemTime::now();
+    if let Some(hit) = secret_cache.and_then(|c| c.get(secret_name, version_stage, now)) {
+        return Ok(hit);
+    }
+
+    let started = std::time::Instant::now();
+    let result = client
+        .get_secret_value()
+        .secret_id(secret_name)
+        .version_stage(version_stage)
+        .send()
+        .await;
+    let response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            use aws_sdk_secretsmanager::error::ProvideErrorMetadata;
+
+            let service_error = e.into_service_error();
+            if is_kms_decrypt_failure(
+                service_error.is_decryption_failure(),
+                service_error.code(),
+                service_error.message(),
+            ) {
+                return Err(SmError::KmsDecryptError(secret_name.to_string()));
+            }
+            if service_error.is_resource_not_found_exception() {
+                let candidates = if secret_name.starts_with("arn:") {
+                    Vec::new()
+                } else {
+                    find_candidate_secrets(client, secret_name).await
+                };
+                let message = if candidates.is_empty() {
+                    service_error.to_string()
+                } else {
+                    not_found_message(secret_name, &candidates)
+                };
+                return Err(SmError::NotFound {
+                    secret: secret_name.to_string(),
+                    message,
+                });
+            }
+            return Err(SmError::AwsError(service_error.to_string()));
+        }
+    };
+    tracing::debug!("GetSecretValue '{}' took {:?}", secret_name, started.elapsed());
+
+    let secret = cache::SecretValue {
+        secret_string: response.secret_string,
+        secret_binary: response
+            .secret_binary
+            .map(|b| base64::engine::general_purpose::STANDARD.encode(b.as_ref())),
+        version_id: response.version_id,
+    };
+
+    if let Some(c) = secret_cache {
+        if let Err(e) = c.put(secret_name, version_stage, &secret, now) {
+            tracing::warn!("failed to cache secret '{}': {}", secret_name, e);
+        }
+    }
+
+    Ok(secret)
+}
+
+/// Whether a GetSecretValue failure comes from KMS: either a `DecryptionFailure`
+/// or an `AccessDeniedException` that mentions the KMS key.
+fn is_kms_decrypt_failure(decryption_failure: bool, code: Option<&str>, message: Option<&str>) -> bool {
+    decryption_failure
+        || (code == Some("AccessDeniedException")
+            && message.is_some_and(|m| m.to_lowercase().contains("kms")))
+}
+
+/// Secrets whose names start with `secret_name`, as `(name, ARN)` pairs. Used to
+/// suggest candidates when a partial name is not found; lookup failures yield none.
+async fn find_candidate_secrets(
+    client: &aws_sdk_secretsmanager::Client,
+    secret_name: &str,
+) -> Vec<(String, String)> {
+    use aws_sdk_secretsmanager::types::{Filter, FilterNameStringType};
+
+    let filter = Filter::builder()
+        .key(FilterNameStringType::Name)
+        .values(secret_name)
+        .build();
+    let mut candidates = Vec::new();
     let mut next_token: Option<String> = None;
+    loop {
+        let mut request = client.list_secrets().filters(filter.clone());
+        if let Some(token) = next_token {
+            request = request.next_token(token);
+        }
+        let Ok(response) = request.send().await else {
+            break;
+        };
+        candidates.extend(
+            response
+                .secret_list
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|s| Some((s.name?, s.arn?)))
+                .filter(|(name, _)| name.starts_with(secret_name)),
+        );
+        next_token = response.next_token;
+        if next_token.is_none() {
+            break;
+        }
+    }
+    candidates.sort();
+    candidates
+}
+
+/// Not-found error listing secrets the user may have meant, with their ARNs.
+fn not_found_message(secret_name: &str, candidates: &[(String, String)]) -> String {
+    let mut message = format!(
+        "Secret '{}' not found. Did you mean one of these? Pass the full name or ARN:",
+        secret_name
+    );
+    for (name, arn) in candidates {
+        message.push_str(&format!("\n  - {} ({})", name, arn));
+    }
+    message
+}
+
+/// A `--tag key[=value]` filter for `list`.
+#[derive(Clone, Debug, PartialEq)]
+struct TagFilter {
+    key: String,
+    value: Option<String>,
+}
+
+impl TagFilter {
+    /// Server-side `tag-key`/`tag-value` filters. AWS matches keys and values
+    /// independently, so results are re-checked with [`TagFilter::matches`].
+    fn to_aws_filters(&self) -> Vec<aws_sdk_secretsmanager::types::Filter> {
+        use aws_sdk_secretsmanager::types::{Filter, FilterNameStringType};
+
+        let mut filters = vec![Filter::builder()
+            .key(FilterNameStringType::TagKey)
+            .values(&self.key)
+            .build()];
+        if let Some(value) = &self.value {
+            filters.push(
+                Filter::builder()
+                    .key(FilterNameStringType::TagValue)
+                    .values(value)
+                    .build(),
+            );
+        }
+        filters
+    }
+
+    fn matches(&self, entry: &SecretListEntry) -> bool {
+        entry.tags().iter().any(|tag| {
+            tag.key() == Some(self.key.as_str())
+                && self.value.as_deref().is_none_or(|v| tag.value() == Some(v))
+        })
+    }
+}
+
+fn parse_tag_filter(s: &str) -> Result<TagFilter, String> {
+    let (key, value) = match s.split_once('=') {
+        Some((key, value)) => (key, Some(value.to_string())),
+        None => (s, None),
+    };
+    if key.is_empty() {
+        return Err("tag key must not be empty".to_string());
+    }
+    Ok(TagFilter { key: key.to_string(), value })
+}
+
+/// Parse a `--since` duration: a whole number followed by `s`, `m`, `h`, `d`, or `w`.
+fn parse_since(s: &str) -> Result<std::time::Duration, String> {
+    let unit_at = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (amount, unit) = s.split_at(unit_at);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("invalid duration '{}': expected e.g. 24h or 7d", s))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("invalid duration unit in '{}': use s, m, h, d, or w", s)),
+    };
+    Ok(std::time::Duration::from_secs(amount * seconds))
+}
+
+/// Unix time `since` before `now`, the oldest change `--since` keeps.
+fn changed_cutoff(now: std::time::SystemTime, since: std::time::Duration) -> i64 {
+    let now = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    now.saturating_sub(since.as_secs()) as i64
+}
+
+/// Whether a secret changed at or after `cutoff`. Secrets with no recorded
+/// change date are dropped, since they cannot be shown to be recent.
+fn changed_since(entry: &SecretListEntry, cutoff: i64) -> bool {
+    entry.last_changed_date().is_some_and(|d| d.secs() >= cutoff)
+}
+
+/// Collect all secrets whose names pass the name filter and that carry every
+/// tag in `tags`, following pagination. Tags are also sent as server-side filters
+/// to cut down the pages fetched. Entries keep the order AWS returned them in.
+async fn fetch_secret_entries(
+    client: &aws_sdk_secretsmanager::Client,
+    filter: Option<&NameFilter>,
+    tags: &[TagFilter],
+) -> Result<Vec<SecretListEntry>, SmError> {
+    fetch_secret_entries_from(client, filter, tags, None).await
+}
+
+/// [`fetch_secret_entries`], starting at the page `start_token` points to. When a
+/// page fails, the token for it is printed under `--verbose` so the listing can be
+/// resumed with `list --start-token`.
+#[tracing::instrument(level = "debug", name = "list_secrets", skip_all)]
+async fn fetch_secret_entries_from(
+    client: &aws_sdk_secretsmanager::Client,
+    filter: Option<&NameFilter>,
+    tags: &[TagFilter],
+    start_token: Option<&str>,
+) -> Result<Vec<SecretListEntry>, SmError> {
+    let aws_filters: Vec<_> = tags.iter().flat_map(TagFilter::to_aws_filters).collect();
+    let mut secrets = Vec::new();
+    let mut next_token: Option<String> = start_token.map(str::to_string);
+    let mut pages = 0;
+    let started = std::time::Instant::now();
 
     loop {
         let mut request = client.list_secrets();
 
-        if let Some(token) = next_token {
+        if !aws_filters.is_empty() {
+            request = request.set_filters(Some(aws_filters.clone()));
+        }
+
+        if let Some(token) = &next_token {
             request = request.next_token(token);
         }
 
-        let response = request
-            .send()
-            .await
-            .map_err(|e| SmError::AwsError(e.to_string()))?;
+        let page_started = std::time::Instant::now();
+        let response = request.send().await.map_err(|e| {
+            if let Some(token) = &next_token {
+                tracing::debug!("ListSecrets page {} failed; resume with --start-token {}", pages + 1, token);
+            }
+            SmError::AwsError(e.to_string())
+        })?;
+        pages += 1;
+        tracing::debug!("ListSecrets page {} took {:?}", pages, page_started.elapsed());
 
         if let Some(secret_list) = response.secret_list {
             secrets.extend(
-                secret_list
-                    .into_iter()
-                    .filter_map(|s| s.name)
-                    .filter(|name| {
-                        filter
-                            .map(|f| name.to_lowercase().contains(&f.to_lowercase()))
-                            .unwrap_or(true)
-                    }),
+                secret_list.into_iter().filter(|s| {
+                    s.name()
+                        .is_some_and(|name| filter.map(|f| f.matches(name)).unwrap_or(true))
+                        && tags.iter().all(|t| t.matches(s))
+                }),
             );
         }
 
@@ -358,17 +2263,747 @@ async fn list_secrets(
         }
     }
 
-    secrets.sort();
+    tracing::debug!("Walked {} ListSecrets page(s) in {:?}", pages, started.elapsed());
+    Ok(secrets)
+}
+
+/// A `--filter` value: a substring by default, or a glob with `--glob`.
+enum NameFilter {
+    Substring { pattern: String, case_sensitive: bool },
+    Glob(globset::GlobMatcher),
+}
+
+impl NameFilter {
+    /// Build the filter, rejecting invalid glob patterns before any API call.
+    fn new(pattern: &str, glob: bool, case_sensitive: bool) -> Result<Self, SmError> {
+        if !glob {
+            return Ok(NameFilter::Substring { pattern: pattern.to_string(), case_sensitive });
+        }
+        let glob = globset::GlobBuilder::new(pattern)
+            .case_insensitive(!case_sensitive)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| SmError::ParseError(format!("invalid glob pattern '{}': {}", pattern, e)))?;
+        Ok(NameFilter::Glob(glob.compile_matcher()))
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            NameFilter::Substring { pattern, case_sensitive: true } => name.contains(pattern.as_str()),
+            NameFilter::Substring { pattern, case_sensitive: false } => {
+                name.to_lowercase().contains(&pattern.to_lowercase())
+            }
+            NameFilter::Glob(matcher) => matcher.is_match(name),
+        }
+    }
+}
+
+/// Fetch every secret matching `filter`, writing each one to a file named after
+/// the secret. Failures are reported per secret and summarized at the end.
+async fn get_all_secrets(
+    client: &aws_sdk_secretsmanager::Client,
+    filter: Option<&str>,
+    base: &GetOptions<'_>,
+) -> Result<(), SmError> {
+    let names = all_secret_names(client, filter).await?;
+    get_each_secret(client, &names, base).await
+}
+
+/// Names of every secret matching `filter`, sorted.
+async fn all_secret_names(
+    client: &aws_sdk_secretsmanager::Client,
+    filter: Option<&str>,
+) -> Result<Vec<String>, SmError> {
+    let name_filter = filter.map(|f| NameFilter::new(f, false, false)).transpose()?;
+    let mut names: Vec<String> = fetch_secret_entries(client, name_filter.as_ref(), &[])
+        .await?
+        .into_iter()
+        .filter_map(|s| s.name)
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Fetch each named secret into its own file named after the secret, up to
+/// `--max-concurrency` at a time. A failure is reported and the batch
+/// continues; failed names are summarized at the end.
+async fn get_each_secret(
+    client: &aws_sdk_secretsmanager::Client,
+    names: &[String],
+    base: &GetOptions<'_>,
+) -> Result<(), SmError> {
+    if names.is_empty() {
+        ui::info!("No secrets found.");
+        return Ok(());
+    }
+
+    let results = run_bounded(names, base.max_concurrency, |name| async move {
+        let stem = output::sanitize_filename(aws_client::secret_basename(name));
+        let opts = GetOptions {
+            secret_names: std::slice::from_ref(name),
+            name_stem: Some(&stem),
+            ..*base
+        };
+        get_secret(client, &opts).await
+    })
+    .await;
+
+    let mut failed = Vec::new();
+    for (name, result) in names.iter().zip(results) {
+        if let Err(e) = result {
+            ui::print_error(format!("failed to fetch '{}': {}", name, e));
+            failed.push(name.as_str());
+        }
+    }
 
+    if !failed.is_empty() {
+        return Err(SmError::AwsError(format!(
+            "{} of {} secrets could not be fetched: {}",
+            failed.len(),
+            names.len(),
+            failed.join(", ")
+        )));
+    }
+
+    Ok(())
+}
+
+/// Print the KMS key that encrypts each secret, for `get --with-kms-info`.
+#[tracing::instrument(level = "debug", skip_all)]
+async fn report_kms_keys(
+    client: &aws_sdk_secretsmanager::Client,
+    secret_names: &[String],
+    limit: usize,
+) -> Result<(), SmError> {
+    let key_ids = run_bounded(secret_names, limit, |name| async move {
+        client
+            .describe_secret()
+            .secret_id(name)
+            .send()
+            .await
+            .map(|response| response.kms_key_id)
+            .map_err(|e| SmError::AwsError(e.to_string()))
+    })
+    .await;
+    for (name, key_id) in secret_names.iter().zip(key_ids) {
+        tracing::info!("KMS key for '{}': {}", name, kms_key_label(key_id?.as_deref()));
+    }
+    Ok(())
+}
+
+/// DescribeSecret leaves `KmsKeyId` out when the secret uses the AWS managed key.
+fn kms_key_label(key_id: Option<&str>) -> &str {
+    key_id.unwrap_or("aws/secretsmanager (AWS managed key)")
+}
+
+/// Run `f` over `items` with at most `limit` futures in flight, keeping the
+/// results in input order.
+async fn run_bounded<'a, T, R, Fut>(items: &'a [T], limit: usize, f: impl FnMut(&'a T) -> Fut) -> Vec<R>
+where
+    Fut: std::future::Future<Output = R>,
+{
+    use futures::stream::{self, StreamExt};
+
+    stream::iter(items).map(f).buffered(limit.max(1)).collect().await
+}
+
+fn parse_max_concurrency(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(0) => Err("must be at least 1".to_string()),
+        Ok(n) => Ok(n),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Secret names from a `--batch-file`: one per line, trimmed, skipping blank
+/// lines and `#` comments.
+fn parse_name_list(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Where `--to-ssm` writes: an SSM client and the parameter path prefix.
+#[derive(Clone, Copy)]
+struct SsmTarget<'a> {
+    client: &'a aws_sdk_ssm::Client,
+    prefix: &'a str,
+}
+
+/// Name of the parameter `key` is written to under `prefix`: `/myapp` and
+/// `/myapp/` both give `/myapp/DB_HOST`.
+fn ssm_parameter_name(prefix: &str, key: &str) -> String {
+    format!("{}/{}", prefix.trim_end_matches('/'), key)
+}
+
+/// Write every key of `data` as a SecureString parameter, overwriting existing
+/// ones. Parameters are written one at a time, since PutParameter is heavily
+/// rate limited; throttled calls are retried by the SDK (see `--max-retries`).
+/// Empty values, which SSM rejects, are skipped with a warning. Returns the
+/// number of parameters written.
+#[tracing::instrument(level = "debug", skip_all, fields(prefix = %ssm.prefix))]
+async fn write_to_ssm(ssm: &SsmTarget<'_>, data: &Map<String, Value>) -> Result<usize, SmError> {
+    use aws_sdk_ssm::types::ParameterType;
+
+    let mut written = 0;
+    for (key, value) in data {
+        let name = ssm_parameter_name(ssm.prefix, key);
+        let value = sm2env::format_value(value);
+        if value.is_empty() {
+            tracing::warn!("skipping '{}': SSM parameters cannot be empty", name);
+            continue;
+        }
+        ssm.client
+            .put_parameter()
+            .name(&name)
+            .value(value)
+            .r#type(ParameterType::SecureString)
+            .overwrite(true)
+            .send()
+            .await
+            .map_err(|e| {
+                SmError::AwsError(format!(
+                    "failed to write '{}' after {} of {} parameter(s): {}",
+                    name,
+                    written,
+                    data.len(),
+                    e
+                ))
+            })?;
+        tracing::debug!("Wrote parameter {}", name);
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// What `--keyring` stores: the raw text of an unmodified plain-text secret,
+/// otherwise the key-value map serialized as compact JSON.
+fn keyring_payload(plain_text: Option<String>, data: &Map<String, Value>) -> Result<String, SmError> {
+    match plain_text {
+        Some(text) => Ok(text),
+        None => converters::json::convert(data, true),
+    }
+}
+
+/// Env-style output printed to the terminal is masked unless `--reveal` is given.
+/// File output is never masked, and neither is stdout redirected to a file or
+/// pipe (`--file - > .env`, `eval "$(...)"`).
+fn should_mask(output_format: &OutputFormat, to_stdout: bool, stdout_is_tty: bool, reveal: bool) -> bool {
+    to_stdout
+        && stdout_is_tty
+        && !reveal
+        && matches!(output_format, OutputFormat::Stdout | OutputFormat::Env | OutputFormat::Direnv)
+}
+
+/// Render the secrets listing (or just their number with `count`) for printing.
+async fn list_secrets(
+    client: &aws_sdk_secretsmanager::Client,
+    filter: Option<&NameFilter>,
+    tags: &[TagFilter],
+    cutoff: Option<i64>,
+    sort_by: Option<SortBy>,
+    count: bool,
+    start_token: Option<&str>,
+) -> Result<String, SmError> {
+    let mut entries = fetch_secret_entries_from(client, filter, tags, start_token).await?;
+    if let Some(cutoff) = cutoff {
+        entries.retain(|e| changed_since(e, cutoff));
+    }
+    if count {
+        return Ok(format!("{}\n", entries.len()));
+    }
+    if let Some(sort_by) = sort_by {
+        sort_secrets(&mut entries, sort_by);
+    }
+    let secrets: Vec<&str> = entries.iter().filter_map(|s| s.name()).collect();
+
+    let mut listing = String::new();
     if secrets.is_empty() {
-        println!("No secrets found.");
+        push_info(&mut listing, "No secrets found.");
     } else {
-        println!("Available secrets:");
+        push_info(&mut listing, "Available secrets:");
         for secret in &secrets {
-            println!("- {}", secret);
+            listing.push_str(&format!("- {}\n", ui::name(secret)));
+        }
+        push_info(&mut listing, &format!("\nTotal: {} secrets", ui::count(secrets.len())));
+    }
+
+    Ok(listing)
+}
+
+/// Append an informational line to rendered output unless `--quiet` is set,
+/// the buffered counterpart of [`ui::info!`].
+fn push_info(out: &mut String, line: &str) {
+    if !ui::is_quiet() {
+        out.push_str(line);
+        out.push('\n');
+    }
+}
+
+/// Print the keys that differ between a secret and a local file's parsed
+/// contents, failing with [`SmError::DriftDetected`] when there are any.
+async fn diff_secret(
+    client: &aws_sdk_secretsmanager::Client,
+    secret_name: &str,
+    version_stage: &str,
+    local: &Map<String, Value>,
+    reveal: bool,
+) -> Result<(), SmError> {
+    let remote = fetch_secret_map(client, secret_name, version_stage).await?;
+
+    let changes = sm2env::diff::diff_maps(&remote, local);
+    if changes.is_empty() {
+        ui::info!("No differences.");
+        return Ok(());
+    }
+    for line in diff_lines(&changes, reveal) {
+        println!("{}", ui::diff_line(&line));
+    }
+    Err(SmError::DriftDetected(changes.len()))
+}
+
+/// Print the keys that differ between a secret's AWSPREVIOUS and AWSCURRENT
+/// versions, e.g. to check what a rotation changed. `+` keys exist only in the
+/// current version and `-` keys only in the previous one.
+async fn compare_version_stages(
+    client: &aws_sdk_secretsmanager::Client,
+    secret_name: &str,
+    reveal: bool,
+) -> Result<(), SmError> {
+    let (previous, current) = futures::try_join!(
+        fetch_secret_map(client, secret_name, "AWSPREVIOUS"),
+        fetch_secret_map(client, secret_name, "AWSCURRENT"),
+    )?;
+
+    let changes = sm2env::diff::diff_maps(&previous, &current);
+    if changes.is_empty() {
+        ui::info!("No differences between AWSPREVIOUS and AWSCURRENT.");
+        return Ok(());
+    }
+    println!("{}", ui::diff_line("--- AWSPREVIOUS"));
+    println!("{}", ui::diff_line("+++ AWSCURRENT"));
+    for line in diff_lines(&changes, reveal) {
+        println!("{}", ui::diff_line(&line));
+    }
+    Ok(())
+}
+
+/// Fetch a secret, uncached, and parse it into key/value pairs. Binary secrets
+/// have no keys and are rejected.
+async fn fetch_secret_map(
+    client: &aws_sdk_secretsmanager::Client,
+    secret_name: &str,
+    version_stage: &str,
+) -> Result<Map<String, Value>, SmError> {
+    let secret = fetch_secret(client, secret_name, version_stage, None).await?;
+    let text = secret.secret_string.ok_or_else(|| {
+        SmError::FormatError(format!("Secret '{}' is binary and has no keys", secret_name))
+    })?;
+    Ok(secret_to_map(detect_secret_format(&text)))
+}
+
+/// Store a local file's keys as a new version of the secret. The changes
+/// against the current value are listed (values masked) before confirming.
+#[tracing::instrument(level = "debug", skip_all, fields(secret = %secret_name))]
+async fn put_secret(
+    client: &aws_sdk_secretsmanager::Client,
+    secret_name: &str,
+    local: Map<String, Value>,
+    merge_remote: bool,
+    yes: bool,
+) -> Result<(), SmError> {
+    // A secret created without a value has no AWSCURRENT version yet
+    let remote = match fetch_secret_map(client, secret_name, "AWSCURRENT").await {
+        Err(SmError::NotFound { .. }) => Map::new(),
+        result => result?,
+    };
+    let payload = put_payload(&remote, local, merge_remote);
+
+    let changes = sm2env::diff::diff_maps(&remote, &payload);
+    if changes.is_empty() {
+        ui::info!("No changes; secret '{}' left as is.", secret_name);
+        return Ok(());
+    }
+    for line in diff_lines(&changes, false) {
+        println!("{}", ui::diff_line(&line));
+    }
+    if !yes && !confirm(&format!("Write {} key(s) to '{}'? [y/N] ", payload.len(), secret_name))? {
+        ui::info!("Aborted.");
+        return Ok(());
+    }
+
+    let response = client
+        .put_secret_value()
+        .secret_id(secret_name)
+        .secret_string(serde_json::to_string(&Value::Object(payload))?)
+        .send()
+        .await
+        .map_err(|e| SmError::AwsError(e.to_string()))?;
+    ui::info!(
+        "Secret '{}' updated (version {})",
+        response.name.as_deref().unwrap_or(secret_name),
+        response.version_id.as_deref().unwrap_or("unknown")
+    );
+    Ok(())
+}
+
+/// The value `put` writes: the local keys alone, or with `merge_remote` the
+/// current secret with the local keys overlaid (local values win).
+fn put_payload(remote: &Map<String, Value>, local: Map<String, Value>, merge_remote: bool) -> Map<String, Value> {
+    if !merge_remote {
+        return local;
+    }
+    let mut merged = remote.clone();
+    merged.extend(local);
+    merged
+}
+
+/// Diff report lines: `+` keys only in the file, `-` keys only in the secret,
+/// `~` changed keys. Values are shown only with `reveal`.
+fn diff_lines(changes: &[(String, sm2env::diff::KeyChange)], reveal: bool) -> Vec<String> {
+    use sm2env::diff::KeyChange;
+
+    let mut lines = Vec::new();
+    for (key, change) in changes {
+        match (change, reveal) {
+            (KeyChange::Added { value }, true) => lines.push(format!("+ {}={}", key, value)),
+            (KeyChange::Removed { value }, true) => lines.push(format!("- {}={}", key, value)),
+            (KeyChange::Changed { old, new }, true) => {
+                lines.push(format!("~ {}", key));
+                lines.push(format!("-   {}", old));
+                lines.push(format!("+   {}", new));
+            }
+            (KeyChange::Added { .. }, false) => lines.push(format!("+ {}", key)),
+            (KeyChange::Removed { .. }, false) => lines.push(format!("- {}", key)),
+            (KeyChange::Changed { .. }, false) => lines.push(format!("~ {}", key)),
         }
-        println!("\nTotal: {} secrets", secrets.len());
+    }
+    lines
+}
+
+/// Settings for building one client per region in `list --all-regions`.
+struct RegionClients<'a> {
+    profile: Option<&'a str>,
+    max_retries: u32,
+    profile_files: aws_client::ProfileFilePaths<'a>,
+}
+
+/// List secrets in every Secrets Manager region, querying up to
+/// [`aws_client::DEFAULT_MAX_CONCURRENCY`] regions at once. Regions that fail
+/// (for example opt-in regions that are not enabled) are skipped with a warning.
+async fn list_secrets_all_regions(
+    clients: &RegionClients<'_>,
+    filter: Option<&NameFilter>,
+    tags: &[TagFilter],
+    cutoff: Option<i64>,
+    sort_by: Option<SortBy>,
+    count: bool,
+) -> Result<String, SmError> {
+    let regions = aws_client::SECRETS_MANAGER_REGIONS;
+    let results = run_bounded(regions, aws_client::DEFAULT_MAX_CONCURRENCY, |region| async move {
+        let client =
+            build_client(Some(region), None, clients.profile, clients.max_retries, clients.profile_files).await;
+        let mut entries = fetch_secret_entries(&client, filter, tags).await?;
+        if let Some(cutoff) = cutoff {
+            entries.retain(|e| changed_since(e, cutoff));
+        }
+        Ok::<_, SmError>(entries)
+    })
+    .await;
+
+    let mut groups = Vec::new();
+    let mut failed = 0;
+    for (region, result) in regions.iter().zip(results) {
+        match result {
+            Ok(mut entries) if !entries.is_empty() => {
+                if let Some(sort_by) = sort_by {
+                    sort_secrets(&mut entries, sort_by);
+                }
+                groups.push((*region, entries));
+            }
+            Ok(_) => {}
+            Err(e) => {
+                failed += 1;
+                tracing::warn!("could not list secrets in {}: {}", region, e);
+            }
+        }
+    }
+    if failed == regions.len() {
+        return Err(SmError::AwsError("could not list secrets in any region".to_string()));
+    }
+    if count {
+        return Ok(format!("{}\n", groups.iter().map(|(_, entries)| entries.len()).sum::<usize>()));
+    }
+
+    let mut listing = String::new();
+    if groups.is_empty() {
+        push_info(&mut listing, "No secrets found.");
+        return Ok(listing);
+    }
+    push_info(&mut listing, "Available secrets:");
+    let mut total = 0;
+    for (region, entries) in &groups {
+        listing.push_str(&format!("{}:\n", region));
+        for name in entries.iter().filter_map(|s| s.name()) {
+            listing.push_str(&format!("- {}\n", ui::name(name)));
+            total += 1;
+        }
+    }
+    push_info(
+        &mut listing,
+        &format!("\nTotal: {} secrets in {} regions", ui::count(total), ui::count(groups.len())),
+    );
+
+    Ok(listing)
+}
+
+/// Sort list entries in place. Secrets without the date sort first.
+fn sort_secrets(entries: &mut [SecretListEntry], sort_by: SortBy) {
+    match sort_by {
+        SortBy::Name => entries.sort_by(|a, b| a.name().cmp(&b.name())),
+        SortBy::Created => entries.sort_by_key(|s| s.created_date().map(|d| d.secs())),
+        SortBy::Changed => entries.sort_by_key(|s| s.last_changed_date().map(|d| d.secs())),
+    }
+}
+
+/// Rewrite the output of `opts` (a single secret) whenever its AWSCURRENT
+/// version changes, checking every `interval` until Ctrl-C. A failed check
+/// after the first write is reported and retried on the next tick.
+async fn watch_secret(
+    client: &aws_sdk_secretsmanager::Client,
+    opts: &GetOptions<'_>,
+    interval: std::time::Duration,
+) -> Result<(), SmError> {
+    let secret_name = &opts.secret_names[0];
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    let mut current: Option<String> = None;
+
+    ui::info!("Watching '{}' every {}s (Ctrl-C to stop)", secret_name, interval.as_secs());
+    loop {
+        let check = async {
+            ticker.tick().await;
+            let version = current_version_id(client, secret_name).await?;
+            if version.is_some() && version == current {
+                return Ok(None);
+            }
+            get_secret(client, opts).await?;
+            Ok::<_, SmError>(Some(version))
+        };
+        tokio::select! {
+            _ = &mut ctrl_c => break,
+            result = check => match result {
+                Ok(Some(version)) => {
+                    let label = version.as_deref().unwrap_or("unknown");
+                    ui::info!("Wrote version {} of '{}'", label, secret_name);
+                    current = version;
+                }
+                Ok(None) => {}
+                Err(e) if current.is_some() => tracing::warn!("{} (retrying in {}s)", e, interval.as_secs()),
+                Err(e) => return Err(e),
+            },
+        }
+    }
+    ui::info!("Stopped watching '{}'", secret_name);
+    Ok(())
+}
+
+/// The version ID currently labelled AWSCURRENT, from DescribeSecret.
+#[tracing::instrument(level = "debug", skip_all, fields(secret = %secret_name))]
+async fn current_version_id(
+    client: &aws_sdk_secretsmanager::Client,
+    secret_name: &str,
+) -> Result<Option<String>, SmError> {
+    let response = client
+        .describe_secret()
+        .secret_id(secret_name)
+        .send()
+        .await
+        .map_err(|e| SmError::AwsError(e.to_string()))?;
+    Ok(response
+        .version_ids_to_stages
+        .and_then(|versions| current_version(&versions).map(str::to_string)))
+}
+
+/// The version ID whose stages include AWSCURRENT.
+fn current_version(versions: &std::collections::HashMap<String, Vec<String>>) -> Option<&str> {
+    versions
+        .iter()
+        .find(|(_, stages)| stages.iter().any(|s| s == "AWSCURRENT"))
+        .map(|(id, _)| id.as_str())
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(secret = %secret_name))]
+async fn describe_secret(
+    client: &aws_sdk_secretsmanager::Client,
+    secret_name: &str,
+    output_format: &MetadataFormat,
+) -> Result<(), SmError> {
+    let response = client
+        .describe_secret()
+        .secret_id(secret_name)
+        .send()
+        .await
+        .map_err(|e| SmError::AwsError(e.to_string()))?;
+
+    let date = |d: Option<aws_sdk_secretsmanager::primitives::DateTime>| {
+        d.map(|d| Value::String(d.to_string())).unwrap_or(Value::Null)
+    };
+    let tags: Map<String, Value> = response
+        .tags
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|t| Some((t.key?, Value::String(t.value.unwrap_or_default()))))
+        .collect();
+
+    let mut metadata = Map::new();
+    metadata.insert("name".to_string(), response.name.map(Value::String).unwrap_or(Value::Null));
+    metadata.insert("arn".to_string(), response.arn.map(Value::String).unwrap_or(Value::Null));
+    metadata.insert(
+        "description".to_string(),
+        response.description.map(Value::String).unwrap_or(Value::Null),
+    );
+    metadata.insert(
+        "kms_key_id".to_string(),
+        response.kms_key_id.map(Value::String).unwrap_or(Value::Null),
+    );
+    metadata.insert(
+        "rotation_enabled".to_string(),
+        Value::Bool(response.rotation_enabled.unwrap_or(false)),
+    );
+    metadata.insert("last_rotated_date".to_string(), date(response.last_rotated_date));
+    metadata.insert("last_changed_date".to_string(), date(response.last_changed_date));
+    metadata.insert("last_accessed_date".to_string(), date(response.last_accessed_date));
+    metadata.insert("tags".to_string(), Value::Object(tags));
+
+    match output_format {
+        MetadataFormat::Json => println!("{}", serde_json::to_string_pretty(&Value::Object(metadata))?),
+        MetadataFormat::Text => print!("{}", format_metadata_text(&metadata)),
+    }
+
+    Ok(())
+}
+
+/// Render metadata as aligned `Field: value` lines; missing values print as `-`.
+fn format_metadata_text(metadata: &Map<String, Value>) -> String {
+    let width = metadata.keys().map(|k| k.len()).max().unwrap_or(0);
+    let mut content = String::new();
+    for (key, value) in metadata {
+        let value_str = match value {
+            Value::Null => "-".to_string(),
+            Value::String(s) => s.clone(),
+            Value::Object(obj) if obj.is_empty() => "-".to_string(),
+            Value::Object(obj) => obj
+                .iter()
+                .map(|(k, v)| format!("{}={}", k, v.as_str().unwrap_or_default()))
+                .collect::<Vec<_>>()
+                .join(", "),
+            other => other.to_string(),
+        };
+        content.push_str(&format!("{:<width$} {}\n", format!("{}:", key), value_str, width = width + 1));
+    }
+    content
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(secret = %secret_name))]
+async fn rotate_secret(
+    client: &aws_sdk_secretsmanager::Client,
+    secret_name: &str,
+    rotation_lambda_arn: Option<&str>,
+    immediate: bool,
+) -> Result<(), SmError> {
+    use aws_sdk_secretsmanager::error::ProvideErrorMetadata;
+
+    let mut request = client.rotate_secret().secret_id(secret_name);
+    if let Some(arn) = rotation_lambda_arn {
+        request = request.rotation_lambda_arn(arn);
+    }
+    if immediate {
+        request = request.rotate_immediately(true);
+    }
+
+    let response = request.send().await.map_err(|e| {
+        let service_error = e.into_service_error();
+        let missing_lambda = service_error.is_invalid_request_exception()
+            && service_error
+                .message()
+                .is_some_and(|m| m.to_lowercase().contains("lambda"));
+        if missing_lambda && rotation_lambda_arn.is_none() {
+            SmError::AwsError(format!(
+                "Secret '{}' has no rotation Lambda configured. \
+                 Pass --rotation-lambda-arn <arn> to set one.",
+                secret_name
+            ))
+        } else {
+            SmError::AwsError(service_error.to_string())
+        }
+    })?;
+
+    let name = response.name.as_deref().unwrap_or(secret_name);
+    match response.version_id {
+        Some(version_id) => println!("Rotation started for '{}'. New version: {}", name, version_id),
+        None => println!("Rotation started for '{}'", name),
     }
 
     Ok(())
 }
+
+#[tracing::instrument(level = "debug", skip_all, fields(secret = %secret_name))]
+async fn delete_secret(
+    client: &aws_sdk_secretsmanager::Client,
+    secret_name: &str,
+    force: bool,
+    recovery_days: Option<i64>,
+    yes: bool,
+) -> Result<(), SmError> {
+    if !yes {
+        let prompt = if force {
+            format!("Permanently delete '{}' with no recovery window? [y/N] ", secret_name)
+        } else {
+            format!("Schedule deletion of '{}'? [y/N] ", secret_name)
+        };
+        if !confirm(&prompt)? {
+            ui::info!("Aborted.");
+            return Ok(());
+        }
+    }
+
+    let mut request = client.delete_secret().secret_id(secret_name);
+    if force {
+        request = request.force_delete_without_recovery(true);
+    } else if let Some(days) = recovery_days {
+        request = request.recovery_window_in_days(days);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| SmError::AwsError(e.to_string()))?;
+
+    let name = response.name.as_deref().unwrap_or(secret_name);
+    match response.deletion_date {
+        Some(date) => ui::info!("Secret '{}' scheduled for deletion on {}", name, date),
+        None => ui::info!("Secret '{}' deleted", name),
+    }
+
+    Ok(())
+}
+
+/// Ask a yes/no question on stdin; anything other than `y`/`yes` is a no.
+fn confirm(prompt: &str) -> Result<bool, SmError> {
+    use std::io::Write;
+
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(is_affirmative(&answer))
+}
+
+fn is_affirmative(answer: &str) -> bool {
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
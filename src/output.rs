@@ -1,5 +1,47 @@
 use crate::errors::SmError;
-use std::path::Path;
+use crate::OutputFormat;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long `get` waits for a reader to open a FIFO target (`--fifo-timeout`).
+pub const DEFAULT_FIFO_TIMEOUT_SECS: u64 = 10;
+
+/// Where `get` writes its output, as chosen on the command line.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OutputTarget<'a> {
+    pub file: Option<&'a str>,
+    pub output_dir: Option<&'a str>,
+    /// Replaces the default filename with `<stem>.<extension>` (used by `get --all`)
+    pub name_stem: Option<&'a str>,
+    /// Appended to env filenames, e.g. `local` gives `.env.local`
+    pub env_suffix: Option<&'a str>,
+}
+
+impl OutputTarget<'_> {
+    /// Resolve where output in `format` goes; `None` means stdout. `--file -` always
+    /// means stdout, and stdout or a rendered template (no format) print when no
+    /// file is given. Keys print too, unless `--output-dir` or a per-secret name
+    /// asks for files (`.env.example`, `<stem>.env.example`).
+    pub fn resolve(&self, format: Option<&OutputFormat>) -> Option<PathBuf> {
+        match (self.file, format) {
+            (Some("-"), _) => None,
+            (Some(f), _) => Some(PathBuf::from(f)),
+            (None, None) | (None, Some(OutputFormat::Stdout)) => None,
+            (None, Some(OutputFormat::Keys)) if self.output_dir.is_none() && self.name_stem.is_none() => None,
+            (None, Some(fmt)) => {
+                let suffix = match (fmt, self.env_suffix) {
+                    (OutputFormat::Env, Some(sfx)) => format!(".{}", sfx),
+                    _ => String::new(),
+                };
+                let file_name = match self.name_stem {
+                    Some(stem) => format!("{}.{}{}", stem, fmt.extension(), suffix),
+                    None => format!("{}{}", fmt.default_filename(), suffix),
+                };
+                Some(Path::new(self.output_dir.unwrap_or_default()).join(file_name))
+            }
+        }
+    }
+}
 
 /// Validate that a path is safe to write to.
 /// Rejects paths containing `..` components or absolute paths outside cwd.
@@ -26,6 +68,14 @@ pub fn validate_path(path: &Path) -> Result<(), SmError> {
     Ok(())
 }
 
+/// Turn a secret name into a safe file name stem, e.g. `prod/db` -> `prod_db`.
+/// Anything other than ASCII letters, digits, `-`, `_`, and `.` becomes `_`.
+pub fn sanitize_filename(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect()
+}
+
 /// Write content to the given path with restricted permissions (0600 on Unix),
 /// or print to stdout if path is None.
 pub fn write_output(content: &str, path: Option<&Path>) -> Result<(), SmError> {
@@ -41,24 +91,179 @@ pub fn write_output(content: &str, path: Option<&Path>) -> Result<(), SmError> {
     }
 }
 
+/// Append content to the given path, creating it (0600 on Unix) if needed.
+/// A newline is inserted first when the existing file does not end with one,
+/// so the previous last line and the new first line never run together.
+pub fn append_output(content: &str, path: &Path) -> Result<(), SmError> {
+    use std::io::{Read, Seek, SeekFrom, Write};
+
+    validate_path(path)?;
+    let mut file = open_append(path)?;
+    let len = file.metadata()?.len();
+    if len > 0 {
+        let mut last = [0u8; 1];
+        file.seek(SeekFrom::Start(len - 1))?;
+        file.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            file.write_all(b"\n")?;
+        }
+    }
+    file.write_all(content.as_bytes())?;
+    Ok(())
+}
+
 #[cfg(unix)]
-fn write_secure(path: &Path, content: &str) -> Result<(), SmError> {
-    use std::fs::OpenOptions;
-    use std::io::Write;
+fn open_append(path: &Path) -> Result<std::fs::File, SmError> {
     use std::os::unix::fs::OpenOptionsExt;
 
-    let mut file = OpenOptions::new()
-        .write(true)
+    Ok(std::fs::OpenOptions::new()
+        .read(true)
+        .append(true)
         .create(true)
-        .truncate(true)
         .mode(0o600)
-        .open(path)?;
-    file.write_all(content.as_bytes())?;
+        .open(path)?)
+}
+
+#[cfg(not(unix))]
+fn open_append(path: &Path) -> Result<std::fs::File, SmError> {
+    Ok(std::fs::OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create(true)
+        .open(path)?)
+}
+
+/// Stream `value` as JSON to `path` with the same permissions and atomicity as
+/// [`write_output`], without building the whole document as a string first.
+pub fn write_json(path: &Path, value: &serde_json::Value, compact: bool) -> Result<(), SmError> {
+    validate_path(path)?;
+    write_atomic_with(path, |file| {
+        use std::io::Write;
+        let mut writer = std::io::BufWriter::new(file);
+        crate::converters::json::write(&mut writer, value, compact)?;
+        writer.flush()
+    })
+}
+
+/// Whether `path` is an existing named pipe. Such targets are written in place
+/// with [`write_fifo`]; the atomic rename would replace the pipe with a file.
+#[cfg(unix)]
+pub fn is_fifo(path: &Path) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+
+    std::fs::metadata(path).is_ok_and(|m| m.file_type().is_fifo())
+}
+
+#[cfg(not(unix))]
+pub fn is_fifo(_path: &Path) -> bool {
+    false
+}
+
+/// Write content into a named pipe. Opening for writing would block until a
+/// reader attaches, so the open is polled instead and fails once `timeout` passes
+/// with nobody reading. The write waits until the reader drains it. Both steps
+/// yield to the runtime, so `--timeout` and Ctrl-C can still cancel them.
+#[cfg(unix)]
+pub async fn write_fifo(path: &Path, content: &[u8], timeout: Duration) -> Result<(), SmError> {
+    use tokio::io::AsyncWriteExt;
+
+    validate_path(path)?;
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut fifo = loop {
+        match tokio::net::unix::pipe::OpenOptions::new().open_sender(path) {
+            Ok(fifo) => break fifo,
+            // ENXIO: no process has the pipe open for reading yet
+            Err(e) if e.raw_os_error() == Some(libc::ENXIO) => {
+                if tokio::time::Instant::now() >= deadline {
+                    return Err(SmError::PathError(format!(
+                        "No reader opened FIFO '{}' within {}s (see --fifo-timeout)",
+                        path.display(),
+                        timeout.as_secs()
+                    )));
+                }
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    };
+    fifo.write_all(content).await?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub async fn write_fifo(path: &Path, _content: &[u8], _timeout: Duration) -> Result<(), SmError> {
+    Err(SmError::PathError(format!(
+        "'{}' is a named pipe, which is only supported on Unix",
+        path.display()
+    )))
+}
+
+/// Create `dir` and any missing parents as owner-only (0700 on Unix). An existing
+/// `dir` is also restricted to 0700; its parents are left alone.
+#[cfg(unix)]
+pub fn create_private_dir(dir: &Path) -> Result<(), SmError> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    std::fs::DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+    std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
     Ok(())
 }
 
 #[cfg(not(unix))]
-fn write_secure(path: &Path, content: &str) -> Result<(), SmError> {
-    std::fs::write(path, content)?;
+pub fn create_private_dir(dir: &Path) -> Result<(), SmError> {
+    std::fs::create_dir_all(dir)?;
     Ok(())
 }
+
+/// Write content with owner-only permissions, without path validation.
+/// The write is atomic: readers see either the old file or the complete new one.
+pub fn write_secure(path: &Path, content: &str) -> Result<(), SmError> {
+    write_atomic_with(path, |file| {
+        use std::io::Write;
+        file.write_all(content.as_bytes())
+    })
+}
+
+/// Atomically replace `path`: `fill` writes a temp file in the same directory,
+/// which is fsynced and renamed over the target. If any step fails the temp
+/// file is removed and an existing target is left untouched.
+pub fn write_atomic_with(
+    path: &Path,
+    fill: impl FnOnce(&mut std::fs::File) -> std::io::Result<()>,
+) -> Result<(), SmError> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| SmError::PathError(format!("'{}' is not a file path", path.display())))?;
+    let tmp_path = path.with_file_name(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    let result = create_private(&tmp_path).and_then(|mut file| {
+        fill(&mut file)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp_path, path)
+    });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    Ok(result?)
+}
+
+#[cfg(unix)]
+fn create_private(path: &Path) -> std::io::Result<std::fs::File> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+}
+
+#[cfg(not(unix))]
+fn create_private(path: &Path) -> std::io::Result<std::fs::File> {
+    std::fs::File::create(path)
+}
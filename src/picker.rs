@@ -0,0 +1,44 @@
+//! Fuzzy secret picker for `--interactive`, built with the `interactive` feature.
+
+use sm2env::errors::SmError;
+use std::io::IsTerminal;
+
+/// The picker reads keys from stdin and draws on stderr, so both must be a
+/// terminal; otherwise it would block waiting for input that never comes.
+/// Stdout is free to be piped.
+pub fn ensure_terminal(stdin_tty: bool, stderr_tty: bool) -> Result<(), SmError> {
+    if stdin_tty && stderr_tty {
+        Ok(())
+    } else {
+        Err(SmError::PromptError(
+            "--interactive needs a terminal; pass the secret name instead".to_string(),
+        ))
+    }
+}
+
+/// Let the user choose one of `names`, typing to fuzzy-filter the list.
+#[cfg(feature = "interactive")]
+pub fn pick(names: &[String]) -> Result<String, SmError> {
+    ensure_terminal(std::io::stdin().is_terminal(), std::io::stderr().is_terminal())?;
+    if names.is_empty() {
+        return Err(SmError::PromptError("No secrets found to choose from".to_string()));
+    }
+    let index = dialoguer::FuzzySelect::new()
+        .with_prompt("Secret")
+        .items(names)
+        .default(0)
+        .interact_opt()
+        .map_err(|e| SmError::PromptError(e.to_string()))?;
+    index
+        .map(|i| names[i].clone())
+        .ok_or_else(|| SmError::PromptError("No secret selected".to_string()))
+}
+
+#[cfg(not(feature = "interactive"))]
+pub fn pick(_names: &[String]) -> Result<String, SmError> {
+    ensure_terminal(std::io::stdin().is_terminal(), std::io::stderr().is_terminal())?;
+    Err(SmError::PromptError(
+        "sm2env was built without the interactive picker; rebuild with `--features interactive`"
+            .to_string(),
+    ))
+}
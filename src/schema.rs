@@ -0,0 +1,51 @@
+//! JSON Schema checks for fetched secrets (`get --schema`).
+
+use crate::detect::SecretFormat;
+use crate::errors::SmError;
+use jsonschema::Validator;
+use serde_json::Value;
+use std::path::Path;
+
+/// Read and compile a JSON Schema file.
+pub fn load(path: &Path) -> Result<Validator, SmError> {
+    let schema: Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    jsonschema::validator_for(&schema)
+        .map_err(|e| SmError::ParseError(format!("invalid schema '{}': {}", path.display(), e)))
+}
+
+/// Each way `instance` breaks the schema, as `<JSON pointer>: <message>`.
+pub fn violations(validator: &Validator, instance: &Value) -> Vec<String> {
+    validator
+        .iter_errors(instance)
+        .map(|e| {
+            let pointer = e.instance_path.to_string();
+            let location = if pointer.is_empty() { "(root)" } else { pointer.as_str() };
+            format!("{}: {}", location, e)
+        })
+        .collect()
+}
+
+/// Check a parsed secret against the schema. Plain-text secrets are not JSON,
+/// so they always fail.
+pub fn validate(validator: &Validator, format: &SecretFormat, secret_name: &str) -> Result<(), SmError> {
+    let instance = match format {
+        SecretFormat::Json(map) => Value::Object(map.clone()),
+        SecretFormat::Array(items) => Value::Array(items.clone()),
+        SecretFormat::PlainText(_) => {
+            return Err(SmError::SchemaError(format!(
+                "secret '{}' is not JSON, so it cannot match the schema",
+                secret_name
+            )))
+        }
+    };
+    let found = violations(validator, &instance);
+    if found.is_empty() {
+        return Ok(());
+    }
+    Err(SmError::SchemaError(format!(
+        "secret '{}' has {} violation(s):\n  {}",
+        secret_name,
+        found.len(),
+        found.join("\n  ")
+    )))
+}
@@ -0,0 +1,123 @@
+use crate::errors::SmError;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Render a template by replacing each `${KEY}` placeholder with the matching
+/// secret value. Unresolved placeholders are an error unless `allow_missing`
+/// is set, in which case they are replaced with an empty string.
+pub fn render(template: &str, data: &Map<String, Value>, allow_missing: bool) -> Result<String, SmError> {
+    let (output, missing) = substitute(template, |key| Ok(data.get(key).map(value_to_string)))?;
+
+    if !missing.is_empty() && !allow_missing {
+        return Err(SmError::FormatError(format!(
+            "Unresolved template placeholders: {}. Use --allow-missing to leave them blank.",
+            missing.join(", ")
+        )));
+    }
+
+    Ok(output)
+}
+
+/// Resolve `${KEY}` references inside string values against other keys of the
+/// same secret. References are followed transitively; cycles are an error, and
+/// so are unknown keys unless `allow_missing` is set (they become empty).
+pub fn interpolate(data: &Map<String, Value>, allow_missing: bool) -> Result<Map<String, Value>, SmError> {
+    let mut resolver = Resolver { data, resolved: HashMap::new(), stack: Vec::new(), missing: Vec::new() };
+    let mut output = Map::new();
+    for (key, value) in data {
+        let value = match value {
+            Value::String(_) => Value::String(resolver.resolve(key)?),
+            other => other.clone(),
+        };
+        output.insert(key.clone(), value);
+    }
+
+    if !resolver.missing.is_empty() && !allow_missing {
+        return Err(SmError::FormatError(format!(
+            "Unresolved references in secret values: {}. Use --allow-missing to leave them blank.",
+            resolver.missing.join(", ")
+        )));
+    }
+
+    Ok(output)
+}
+
+struct Resolver<'a> {
+    data: &'a Map<String, Value>,
+    resolved: HashMap<String, String>,
+    stack: Vec<String>,
+    missing: Vec<String>,
+}
+
+impl Resolver<'_> {
+    fn resolve(&mut self, key: &str) -> Result<String, SmError> {
+        if let Some(done) = self.resolved.get(key) {
+            return Ok(done.clone());
+        }
+        if let Some(pos) = self.stack.iter().position(|k| k == key) {
+            let mut cycle = self.stack[pos..].to_vec();
+            cycle.push(key.to_string());
+            return Err(SmError::FormatError(format!(
+                "Circular reference in secret values: {}",
+                cycle.join(" -> ")
+            )));
+        }
+
+        let raw = value_to_string(&self.data[key]);
+        self.stack.push(key.to_string());
+        let (output, missing) = substitute(&raw, |name| {
+            if self.data.contains_key(name) {
+                self.resolve(name).map(Some)
+            } else {
+                Ok(None)
+            }
+        })?;
+        self.stack.pop();
+
+        for name in missing {
+            if !self.missing.contains(&name) {
+                self.missing.push(name);
+            }
+        }
+        self.resolved.insert(key.to_string(), output.clone());
+        Ok(output)
+    }
+}
+
+/// Replace each `${NAME}` in `text` with `lookup(NAME)`. Names the lookup does not
+/// know are left blank and returned in order. An unclosed `${` is kept verbatim.
+fn substitute(
+    text: &str,
+    mut lookup: impl FnMut(&str) -> Result<Option<String>, SmError>,
+) -> Result<(String, Vec<String>), SmError> {
+    let mut output = String::with_capacity(text.len());
+    let mut missing = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("${") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            // No closing brace: keep the remainder verbatim
+            output.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+        let key = &after[..end];
+        match lookup(key)? {
+            Some(value) => output.push_str(&value),
+            None => missing.push(key.to_string()),
+        }
+        rest = &after[end + 1..];
+    }
+    output.push_str(rest);
+
+    Ok((output, missing))
+}
+
+fn value_to_string(value: &Value) -> String {
+    value
+        .as_str()
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| value.to_string())
+}
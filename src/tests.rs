@@ -1,4 +1,5 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     use crate::converters;
     use base64::Engine;
@@ -23,7 +24,7 @@ mod tests {
     #[test]
     fn test_csv_simple_values() {
         let data = make_map(&[("KEY1", "value1"), ("KEY2", "value2")]);
-        let csv = converters::csv::convert(&data).unwrap();
+        let csv = converters::csv::convert(&data, &Default::default()).unwrap();
         assert!(csv.contains("key,value"));
         assert!(csv.contains("KEY1,value1") || csv.contains("KEY2,value2"));
     }
@@ -31,7 +32,7 @@ mod tests {
     #[test]
     fn test_csv_value_with_comma() {
         let data = make_map(&[("FOO", "a,b")]);
-        let csv = converters::csv::convert(&data).unwrap();
+        let csv = converters::csv::convert(&data, &Default::default()).unwrap();
         // csv crate quotes values containing commas
         assert!(csv.contains("FOO,\"a,b\""));
     }
@@ -39,7 +40,7 @@ mod tests {
     #[test]
     fn test_csv_value_with_double_quote() {
         let data = make_map(&[("FOO", r#"say "hi""#)]);
-        let csv = converters::csv::convert(&data).unwrap();
+        let csv = converters::csv::convert(&data, &Default::default()).unwrap();
         // RFC 4180: the field should be quoted and internal quotes doubled.
         // Accept both LF and CRLF line endings.
         assert!(csv.contains("FOO,") && csv.contains(r#"say ""hi"""#));
@@ -48,11 +49,45 @@ mod tests {
     #[test]
     fn test_csv_value_with_newline() {
         let data = make_map(&[("FOO", "line1\nline2")]);
-        let csv = converters::csv::convert(&data).unwrap();
+        let csv = converters::csv::convert(&data, &Default::default()).unwrap();
         // csv crate wraps values with newlines in quotes
         assert!(csv.contains("\"line1\nline2\""));
     }
 
+    #[test]
+    fn test_csv_output_exposed_on_cli() {
+        use clap::Parser;
+        let cli = crate::Cli::try_parse_from(["sm2env", "get", "app", "--output", "csv"]).unwrap();
+        match cli.command {
+            Some(crate::Commands::Get { output, .. }) => assert!(matches!(output[..], [OutputFormat::Csv])),
+            _ => panic!("Expected get command"),
+        }
+        assert_eq!(OutputFormat::Csv.default_filename(), "secret.csv");
+    }
+
+    #[test]
+    fn test_csv_tab_delimiter_and_no_header() {
+        let data = make_map(&[("A", "x\ty"), ("B", "2")]);
+        let opts = converters::ConvertOptions {
+            csv_delimiter: Some(b'\t'),
+            csv_no_header: true,
+            ..Default::default()
+        };
+        let tsv = converters::csv::convert(&data, &opts).unwrap();
+        // The value containing the delimiter is quoted; no header row
+        assert_eq!(tsv, "A\t\"x\ty\"\nB\t2\n");
+    }
+
+    #[test]
+    fn test_csv_parse_delimiter() {
+        use crate::converters::csv::parse_delimiter;
+        assert_eq!(parse_delimiter(";"), Ok(b';'));
+        assert_eq!(parse_delimiter("\\t"), Ok(b'\t'));
+        assert_eq!(parse_delimiter("\t"), Ok(b'\t'));
+        assert!(parse_delimiter("§").is_err());
+        assert!(parse_delimiter(",,").is_err());
+    }
+
     // ── Task 1.4: detect_secret_format ─────────────────────────────────────────
 
     #[test]
@@ -62,9 +97,9 @@ mod tests {
     }
 
     #[test]
-    fn test_detect_json_array_is_plain_text() {
+    fn test_detect_json_array() {
         let s = r#"["a","b"]"#;
-        assert!(matches!(detect_secret_format(s), SecretFormat::PlainText(_)));
+        assert!(matches!(detect_secret_format(s), SecretFormat::Array(_)));
     }
 
     #[test]
@@ -155,7 +190,7 @@ mod tests {
     #[test]
     fn test_env_converter() {
         let data = make_map(&[("FOO", "bar"), ("BAZ", "qux")]);
-        let result = converters::env::convert(&data);
+        let result = converters::env::convert(&data, &Default::default());
         assert!(result.contains("FOO=bar\n"));
         assert!(result.contains("BAZ=qux\n"));
     }
@@ -163,7 +198,7 @@ mod tests {
     #[test]
     fn test_json_converter() {
         let data = make_map(&[("KEY", "val")]);
-        let result = converters::json::convert(&data).unwrap();
+        let result = converters::json::convert(&data, false).unwrap();
         let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
         assert_eq!(parsed["KEY"], "val");
     }
@@ -171,14 +206,25 @@ mod tests {
     #[test]
     fn test_yaml_converter() {
         let data = make_map(&[("KEY", "val")]);
-        let result = converters::yaml::convert(&data).unwrap();
+        let result = converters::yaml::convert(&data, false, false).unwrap();
         assert!(result.contains("KEY: val"));
     }
 
+    #[test]
+    fn test_yaml_doc_start_marker() {
+        let data = make_map(&[("KEY", "val")]);
+        assert!(!converters::yaml::convert(&data, false, false).unwrap().starts_with("---"));
+        assert_eq!(converters::yaml::convert(&data, true, false).unwrap(), "---\nKEY: val\n");
+
+        let opts = converters::ConvertOptions { yaml_doc_start: true, ..Default::default() };
+        let array = sm2env::format_secret(r#"["a","b"]"#, &OutputFormat::Yaml, &opts).unwrap();
+        assert!(array.starts_with("---\n- a\n"));
+    }
+
     #[test]
     fn test_csv_converter() {
         let data = make_map(&[("KEY", "val")]);
-        let result = converters::csv::convert(&data).unwrap();
+        let result = converters::csv::convert(&data, &Default::default()).unwrap();
         assert!(result.contains("key,value"));
         assert!(result.contains("KEY,val"));
     }
@@ -187,16 +233,32 @@ mod tests {
 
     #[test]
     fn test_filter_case_insensitive() {
-        let name = "PROD-database";
-        let filter = "prod";
-        assert!(name.to_lowercase().contains(&filter.to_lowercase()));
+        let filter = crate::NameFilter::new("prod", false, false).unwrap();
+        assert!(filter.matches("PROD-database"));
     }
 
     #[test]
     fn test_filter_mixed_case() {
-        let name = "prod-api";
-        let filter = "Prod";
-        assert!(name.to_lowercase().contains(&filter.to_lowercase()));
+        let filter = crate::NameFilter::new("Prod", false, false).unwrap();
+        assert!(filter.matches("prod-api"));
+    }
+
+    #[test]
+    fn test_filter_case_sensitive() {
+        let filter = crate::NameFilter::new("prod", false, true).unwrap();
+        assert!(filter.matches("prod/db"));
+        assert!(!filter.matches("Prod/DB"));
+    }
+
+    #[test]
+    fn test_filter_glob() {
+        let filter = crate::NameFilter::new("prod/*/db", true, false).unwrap();
+        assert!(filter.matches("prod/api/db"));
+        assert!(filter.matches("PROD/api/DB"));
+        // `*` stays within one path segment, and the whole name must match
+        assert!(!filter.matches("prod/api/v2/db"));
+        assert!(!filter.matches("prod/api/db-old"));
+        assert!(crate::NameFilter::new("prod/[", true, false).is_err());
     }
 
     // ── Task 6.3: Empty secret handling ───────────────────────────────────────
@@ -272,17 +334,98 @@ format = "json"
         assert_eq!(map["binary_data"], base64_str);
     }
 
-    // ── Task 11.2: JSON arrays treated as plain text ───────────────────────────
+    #[test]
+    fn test_looks_like_base64() {
+        use crate::detect::looks_like_base64;
+        assert!(looks_like_base64("eyJBIjoiMSJ9"));
+        assert!(looks_like_base64("aGVsbG8=\n"));
+        assert!(!looks_like_base64("hello world"));
+        assert!(!looks_like_base64("abc"));
+        assert!(!looks_like_base64(""));
+        assert!(!looks_like_base64(r#"{"A":"1"}"#));
+    }
+
+    #[test]
+    fn test_decode_base64_secret() {
+        use crate::cache::SecretValue;
+        let text = |s: &str| SecretValue { secret_string: Some(s.to_string()), secret_binary: None, version_id: None };
+
+        // base64 of {"A":"1"} becomes a regular string secret
+        let decoded = crate::decode_base64_secret(text("eyJBIjoiMSJ9"));
+        assert_eq!(decoded.secret_string.as_deref(), Some(r#"{"A":"1"}"#));
+        // Non-UTF-8 bytes become a binary secret
+        let decoded = crate::decode_base64_secret(text("/wD+"));
+        assert_eq!(decoded.secret_binary.as_deref(), Some("/wD+"));
+        assert!(decoded.secret_string.is_none());
+        // Anything else is left alone
+        let decoded = crate::decode_base64_secret(text("not base64!"));
+        assert_eq!(decoded.secret_string.as_deref(), Some("not base64!"));
+    }
+
+    #[test]
+    fn test_binary_secret_never_reaches_output_raw() {
+        use clap::ValueEnum;
+
+        // Raw bytes (including an escape sequence) are only ever rendered as base64,
+        // so no output format, terminal or file, can carry them
+        let bytes = [0x1B, b'[', b'2', b'J', 0xFF, 0x00];
+        let mut map = Map::new();
+        map.insert(
+            crate::detect::DEFAULT_BINARY_KEY.to_string(),
+            Value::String(base64::engine::general_purpose::STANDARD.encode(bytes)),
+        );
+        for format in OutputFormat::value_variants() {
+            let out = converters::convert_to_format(&map, format, &Default::default()).unwrap();
+            assert!(
+                out.chars().all(|c| c.is_ascii() && (!c.is_ascii_control() || "\n\r\t".contains(c))),
+                "{} output contains raw bytes",
+                format
+            );
+        }
+    }
+
+    // ── Task 11.2: JSON array secrets ──────────────────────────────────────────
 
     #[test]
-    fn test_json_array_is_plain_text() {
+    fn test_json_array_detected_as_array() {
         let s = r#"["a","b","c"]"#;
         match detect_secret_format(s) {
-            SecretFormat::PlainText(t) => assert_eq!(t, s),
-            _ => panic!("Expected PlainText for JSON array"),
+            SecretFormat::Array(items) => assert_eq!(items.len(), 3),
+            _ => panic!("Expected Array for JSON array"),
         }
     }
 
+    #[test]
+    fn test_json_array_flattened_for_env() {
+        let fmt = detect_secret_format(r#"["a","b"]"#);
+        let map = secret_to_map(fmt);
+        assert_eq!(map["SECRET_0"], "a");
+        assert_eq!(map["SECRET_1"], "b");
+    }
+
+    #[test]
+    fn test_json_array_custom_key() {
+        let items = vec![json!("a"), json!(2)];
+        let env = converters::convert_array(&items, &OutputFormat::Env, "HOST", &Default::default()).unwrap();
+        assert!(env.contains("HOST_0=a\n"));
+        assert!(env.contains("HOST_1=2\n"));
+    }
+
+    #[test]
+    fn test_json_array_kept_as_array_for_json() {
+        let items = vec![json!("a"), json!("b")];
+        let out = converters::convert_array(&items, &OutputFormat::Json, "SECRET", &Default::default()).unwrap();
+        let parsed: Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(parsed, json!(["a", "b"]));
+    }
+
+    #[test]
+    fn test_json_array_stdout_one_per_line() {
+        let items = vec![json!("a"), json!("b")];
+        let out = converters::convert_array(&items, &OutputFormat::Stdout, "SECRET", &Default::default()).unwrap();
+        assert_eq!(out, "a\nb\n");
+    }
+
     // ── Task 11.3: Nested JSON objects ────────────────────────────────────────
 
     #[test]
@@ -295,7 +438,7 @@ format = "json"
     fn test_nested_json_converted_to_env() {
         let json_val = json!({"outer": {"inner": "value"}});
         if let Value::Object(map) = json_val {
-            let env = converters::env::convert(&map);
+            let env = converters::env::convert(&map, &Default::default());
             // Nested object becomes its JSON string representation
             assert!(env.contains("outer="));
         }
@@ -312,13 +455,67 @@ format = "json"
         }
     }
 
+    #[test]
+    fn test_empty_json_secret_warns_by_default() {
+        let names = vec!["app".to_string()];
+        let map = secret_to_map(detect_secret_format("{}"));
+        assert!(crate::ensure_has_keys(&map, &names, false).is_ok());
+        assert!(crate::ensure_has_keys(&make_map(&[("A", "1")]), &names, false).is_ok());
+    }
+
+    #[test]
+    fn test_empty_json_secret_is_rejected_with_strict() {
+        let names = vec!["app".to_string()];
+        let map = secret_to_map(detect_secret_format("{}"));
+        let err = crate::ensure_has_keys(&map, &names, true).unwrap_err();
+        assert!(err.to_string().contains("secret app contains no keys"), "{}", err);
+        assert!(crate::ensure_has_keys(&make_map(&[("A", "1")]), &names, true).is_ok());
+    }
+
     // ── Task 11.5: Multiline values in env format ─────────────────────────────
 
     #[test]
     fn test_multiline_value_in_env_output() {
         let data = make_map(&[("KEY", "line1\nline2")]);
-        let env = converters::env::convert(&data);
-        assert!(env.contains("KEY=line1\nline2\n"));
+        let env = converters::env::convert(&data, &Default::default());
+        assert_eq!(env, "KEY=\"line1\\nline2\"\n");
+
+        let pem = "-----BEGIN KEY-----\nMIIB\\x\"y\n-----END KEY-----\n";
+        let env = converters::env::convert(&make_map(&[("PEM", pem)]), &Default::default());
+        assert_eq!(env, "PEM=\"-----BEGIN KEY-----\\nMIIB\\\\x\\\"y\\n-----END KEY-----\\n\"\n");
+        assert_eq!(env.lines().count(), 1);
+
+        // single-line values are written as before
+        let env = converters::env::convert(&make_map(&[("A", "plain value")]), &Default::default());
+        assert_eq!(env, "A=plain value\n");
+    }
+
+    #[test]
+    fn test_multiline_env_value_round_trips() {
+        let pem = "-----BEGIN KEY-----\nMIIB\\x\"y\n-----END KEY-----\n";
+        let env = converters::env::convert(&make_map(&[("PEM", pem)]), &Default::default());
+        assert_eq!(crate::detect::parse_env_vars(&env)["PEM"], pem);
+        // backslashes outside double quotes are kept literally
+        assert_eq!(crate::detect::parse_env_vars("P=C:\\new")["P"], "C:\\new");
+    }
+
+    #[test]
+    fn test_stdout_and_env_file_output_match() {
+        let data = make_map(&[("KEY", "line1\nline2"), ("Q", "\"quoted\"")]);
+        let opts = converters::ConvertOptions::default();
+        let stdout = converters::convert_to_format(&data, &OutputFormat::Stdout, &opts).unwrap();
+        let env = converters::convert_to_format(&data, &OutputFormat::Env, &opts).unwrap();
+        assert_eq!(stdout, env);
+    }
+
+    #[test]
+    fn test_env_align() {
+        let data = make_map(&[("A", "1"), ("LONG_KEY", "2"), ("MID", "3")]);
+        let opts = converters::ConvertOptions { env_align: true, ..Default::default() };
+        assert_eq!(
+            converters::env::convert(&data, &opts),
+            "A        = 1\nLONG_KEY = 2\nMID      = 3\n"
+        );
     }
 
     // ── Task 11.8: Invalid JSON object case should be handled ─────────────────
@@ -332,26 +529,41 @@ format = "json"
         assert_eq!(map["SECRET_VALUE"], "just a plain string");
     }
 
+    // ── Duplicate env keys ────────────────────────────────────────────────────
+
+    #[test]
+    fn test_duplicate_env_keys() {
+        let text = "A=1\nB=2\nA=3\n# A=4\nA=5\nB=6";
+        assert_eq!(crate::detect::duplicate_env_keys(text), vec!["A", "B"]);
+        // Last value wins when converting
+        let map = crate::detect::parse_env_vars(text);
+        assert_eq!(map.get("A"), Some(&json!("5")));
+        assert_eq!(map.get("B"), Some(&json!("6")));
+        assert!(crate::detect::duplicate_env_keys("A=1\nB=2").is_empty());
+    }
+
+    #[test]
+    fn test_plain_env_output_is_normalized() {
+        let text = "# database\nA=1\nB=2\n\nA=3\n";
+        let map = secret_to_map(detect_secret_format(text));
+        let env = converters::env::convert(&map, &Default::default());
+        assert_eq!(env, "A=3\nB=2\n");
+    }
+
     // ── Backward-compat: existing JSON→env and JSON→csv behavior ──────────────
 
     #[test]
     fn test_json_to_env_format() {
-        let temp = NamedTempFile::new_in(".").unwrap();
-        let path = temp.path().to_path_buf();
-
         let data = make_map(&[("KEY1", "value1"), ("KEY2", "value2")]);
-        let content = converters::convert_to_format(&data, &OutputFormat::Env).unwrap();
-        crate::output::write_output(&content, Some(&path)).unwrap();
-
-        let written = std::fs::read_to_string(&path).unwrap();
-        assert!(written.contains("KEY1=value1\n"));
-        assert!(written.contains("KEY2=value2\n"));
+        let content = converters::convert_to_format(&data, &OutputFormat::Env, &Default::default()).unwrap();
+        assert!(content.contains("KEY1=value1\n"));
+        assert!(content.contains("KEY2=value2\n"));
     }
 
     #[test]
     fn test_json_to_csv_format() {
         let data = make_map(&[("KEY1", "value1"), ("KEY2", "value2")]);
-        let csv = converters::convert_to_format(&data, &OutputFormat::Csv).unwrap();
+        let csv = converters::convert_to_format(&data, &OutputFormat::Csv, &Default::default()).unwrap();
         assert!(csv.contains("key,value"));
         assert!(csv.contains("KEY1,value1"));
         assert!(csv.contains("KEY2,value2"));
@@ -362,7 +574,7 @@ format = "json"
         let text = "KEY1=value1\nKEY2=value2".to_string();
         let fmt = detect_secret_format(&text);
         let map = secret_to_map(fmt);
-        let content = converters::convert_to_format(&map, &OutputFormat::Env).unwrap();
+        let content = converters::convert_to_format(&map, &OutputFormat::Env, &Default::default()).unwrap();
         assert!(content.contains("KEY1=value1\n"));
         assert!(content.contains("KEY2=value2\n"));
     }
@@ -372,9 +584,1982 @@ format = "json"
         let text = "KEY1=value1\nKEY2=value2".to_string();
         let fmt = detect_secret_format(&text);
         let map = secret_to_map(fmt);
-        let csv = converters::convert_to_format(&map, &OutputFormat::Csv).unwrap();
+        let csv = converters::convert_to_format(&map, &OutputFormat::Csv, &Default::default()).unwrap();
         assert!(csv.contains("key,value"));
         assert!(csv.contains("KEY1,value1"));
         assert!(csv.contains("KEY2,value2"));
     }
+
+    // ── Template rendering ────────────────────────────────────────────────────
+
+    #[test]
+    fn test_template_substitutes_placeholders() {
+        let data = make_map(&[("HOST", "db.local"), ("PORT", "5432"), ("UNUSED", "x")]);
+        let out = crate::template::render("url=${HOST}:${PORT}\n", &data, false).unwrap();
+        assert_eq!(out, "url=db.local:5432\n");
+    }
+
+    #[test]
+    fn test_template_missing_placeholder_errors() {
+        let data = make_map(&[("HOST", "db.local")]);
+        let err = crate::template::render("${HOST} ${PASSWORD}", &data, false).unwrap_err();
+        assert!(err.to_string().contains("PASSWORD"));
+    }
+
+    #[test]
+    fn test_template_allow_missing_leaves_blank() {
+        let data = make_map(&[("HOST", "db.local")]);
+        let out = crate::template::render("${HOST}:${PORT}", &data, true).unwrap();
+        assert_eq!(out, "db.local:");
+    }
+
+    #[test]
+    fn test_interpolate_values() {
+        let data = make_map(&[
+            ("DB_USER", "admin"),
+            ("DB_PASS", "p@ss"),
+            ("DB_AUTH", "${DB_USER}:${DB_PASS}"),
+            ("DATABASE_URL", "postgres://${DB_AUTH}@host"),
+        ]);
+        let out = crate::template::interpolate(&data, false).unwrap();
+        assert_eq!(out["DATABASE_URL"], json!("postgres://admin:p@ss@host"));
+        assert_eq!(out["DB_USER"], json!("admin"));
+    }
+
+    #[test]
+    fn test_interpolate_missing_and_cycles() {
+        let data = make_map(&[("URL", "http://${HOST}/")]);
+        let err = crate::template::interpolate(&data, false).unwrap_err();
+        assert!(err.to_string().contains("HOST"));
+        let out = crate::template::interpolate(&data, true).unwrap();
+        assert_eq!(out["URL"], json!("http:///"));
+
+        let data = make_map(&[("A", "${B}"), ("B", "x${A}")]);
+        let err = crate::template::interpolate(&data, false).unwrap_err();
+        assert!(err.to_string().contains("A -> B -> A"), "{}", err);
+    }
+
+    #[test]
+    fn test_allow_missing_requires_template_or_interpolate() {
+        use clap::Parser;
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "app", "--allow-missing"]).is_err());
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "app", "--interpolate", "--allow-missing"]).is_ok());
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "app", "--template", "t", "--interpolate"]).is_ok());
+    }
+
+    // ── Repeatable --output ───────────────────────────────────────────────────
+
+    #[test]
+    fn test_output_repeatable_and_gnu_style() {
+        use clap::Parser;
+        let cli = crate::Cli::try_parse_from(["sm2env", "get", "app", "--output=env", "--output", "json"]).unwrap();
+        match cli.command {
+            Some(crate::Commands::Get { output, .. }) => {
+                assert!(matches!(output.as_slice(), [OutputFormat::Env, OutputFormat::Json]));
+            }
+            _ => panic!("Expected get command"),
+        }
+    }
+
+    #[test]
+    fn test_output_dir_conflicts_with_file() {
+        use clap::Parser;
+        let result = crate::Cli::try_parse_from([
+            "sm2env", "get", "app", "--file", ".env", "--output-dir", "out",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_default_filenames() {
+        assert_eq!(OutputFormat::Env.default_filename(), ".env");
+        assert_eq!(OutputFormat::Json.default_filename(), "secret.json");
+        assert_eq!(OutputFormat::Yaml.default_filename(), "secret.yaml");
+        assert_eq!(OutputFormat::Csv.default_filename(), "secret.csv");
+    }
+
+    // ── Stdout masking ────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_stdout_masked_without_reveal() {
+        let data = make_map(&[("DB_PASSWORD", "hunter2")]);
+        assert!(crate::should_mask(&OutputFormat::Stdout, true, true, false));
+        let masked = converters::mask_values(&data);
+        let out = converters::convert_to_format(&masked, &OutputFormat::Stdout, &Default::default()).unwrap();
+        assert_eq!(out, "DB_PASSWORD=****\n");
+        assert!(!out.contains("hunter2"));
+    }
+
+    #[test]
+    fn test_stdout_shown_with_reveal() {
+        assert!(!crate::should_mask(&OutputFormat::Stdout, true, true, true));
+        assert!(!crate::should_mask(&OutputFormat::Env, true, true, true));
+    }
+
+    #[test]
+    fn test_file_output_never_masked() {
+        assert!(!crate::should_mask(&OutputFormat::Env, false, true, false));
+        assert!(!crate::should_mask(&OutputFormat::Json, true, true, false));
+    }
+
+    #[test]
+    fn test_stdout_to_pipe_never_masked() {
+        // `get -o env --file - > .env`: stdout, but not a terminal
+        let target = sm2env::output::OutputTarget { file: Some("-"), ..Default::default() };
+        let to_stdout = target.resolve(Some(&OutputFormat::Env)).is_none();
+        assert!(to_stdout);
+        for format in [OutputFormat::Env, OutputFormat::Stdout, OutputFormat::Direnv] {
+            assert!(!crate::should_mask(&format, to_stdout, false, false));
+            assert!(crate::should_mask(&format, to_stdout, true, false));
+        }
+    }
+
+    // ── Terraform tfvars output ───────────────────────────────────────────────
+
+    #[test]
+    fn test_tfvars_quotes_strings_and_leaves_scalars() {
+        let data = json!({"name": "app", "port": 5432, "debug": true});
+        let Value::Object(map) = data else { unreachable!() };
+        let out = converters::tfvars::convert(&map);
+        assert!(out.contains("name = \"app\"\n"));
+        assert!(out.contains("port = 5432\n"));
+        assert!(out.contains("debug = true\n"));
+    }
+
+    #[test]
+    fn test_tfvars_escapes_quotes() {
+        let data = make_map(&[("motd", r#"say "hi""#)]);
+        let out = converters::tfvars::convert(&data);
+        assert_eq!(out, "motd = \"say \\\"hi\\\"\"\n");
+    }
+
+    #[test]
+    fn test_tfvars_escapes_backslashes() {
+        let data = make_map(&[("path", r"C:\temp")]);
+        let out = converters::tfvars::convert(&data);
+        assert_eq!(out, "path = \"C:\\\\temp\"\n");
+    }
+
+    #[test]
+    fn test_tfvars_escapes_interpolation() {
+        assert_eq!(converters::tfvars::escape_hcl("${var}"), "$${var}");
+    }
+
+    #[test]
+    fn test_tfvars_skips_keys_that_are_not_identifiers() {
+        let data = make_map(&[("db.host", "h"), ("my-key", "k"), ("1st", "f"), ("has space", "s"), ("_ok", "o")]);
+        assert_eq!(converters::tfvars::convert(&data), "my-key = \"k\"\n_ok = \"o\"\n");
+        assert_eq!(converters::tfvars::invalid_keys(&data), vec!["db.host", "1st", "has space"]);
+    }
+
+    // ── Retry configuration ───────────────────────────────────────────────────
+
+    #[test]
+    fn test_max_retries_default_and_override() {
+        use clap::Parser;
+        let cli = crate::Cli::try_parse_from(["sm2env", "list"]).unwrap();
+        assert!(matches!(cli.command, Some(crate::Commands::List { max_retries: 3, .. })));
+
+        let cli = crate::Cli::try_parse_from(["sm2env", "get", "app", "--max-retries", "7"]).unwrap();
+        assert!(matches!(cli.command, Some(crate::Commands::Get { max_retries: 7, .. })));
+    }
+
+    #[test]
+    fn test_aws_profile_file_flags() {
+        use clap::Parser;
+        let cli = crate::Cli::try_parse_from([
+            "sm2env", "list", "--aws-config-file", "/run/aws/config", "--aws-credentials-file", "/run/aws/credentials",
+        ])
+        .unwrap();
+        let files = cli.profile_files();
+        assert_eq!(files.config, Some("/run/aws/config"));
+        assert_eq!(files.credentials, Some("/run/aws/credentials"));
+        let cli = crate::Cli::try_parse_from(["sm2env", "list"]).unwrap();
+        assert!(cli.profile_files().config.is_none());
+    }
+
+    // ── Delete command ────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_delete_confirmation_answers() {
+        assert!(crate::is_affirmative("y\n"));
+        assert!(crate::is_affirmative(" YES "));
+        assert!(!crate::is_affirmative("\n"));
+        assert!(!crate::is_affirmative("no"));
+    }
+
+    #[test]
+    fn test_delete_force_conflicts_with_recovery_days() {
+        use clap::Parser;
+        let result = crate::Cli::try_parse_from([
+            "sm2env", "delete", "app", "--force", "--recovery-days", "7",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_delete_recovery_days_range() {
+        use clap::Parser;
+        assert!(crate::Cli::try_parse_from(["sm2env", "delete", "app", "--recovery-days", "3"]).is_err());
+        assert!(crate::Cli::try_parse_from(["sm2env", "delete", "app", "--recovery-days", "14"]).is_ok());
+        assert!(crate::Cli::try_parse_from(["sm2env", "delete", "app", "--no-recovery"]).is_ok());
+    }
+
+    // ── Describe command ──────────────────────────────────────────────────────
+
+    #[test]
+    fn test_metadata_text_formatting() {
+        let mut metadata = Map::new();
+        metadata.insert("arn".to_string(), json!("arn:aws:secretsmanager:us-east-1:1:secret:app"));
+        metadata.insert("description".to_string(), Value::Null);
+        metadata.insert("rotation_enabled".to_string(), json!(false));
+        metadata.insert("tags".to_string(), json!({"team": "infra"}));
+        let out = crate::format_metadata_text(&metadata);
+        assert!(out.contains("arn:              arn:aws:secretsmanager:us-east-1:1:secret:app\n"));
+        assert!(out.contains("description:      -\n"));
+        assert!(out.contains("rotation_enabled: false\n"));
+        assert!(out.contains("tags:             team=infra\n"));
+    }
+
+    // ── Env trailing newline ──────────────────────────────────────────────────
+
+    #[test]
+    fn test_env_single_pair_ends_with_one_newline() {
+        let map = secret_to_map(detect_secret_format("KEY=value"));
+        let env = converters::env::convert(&map, &Default::default());
+        assert_eq!(env, "KEY=value\n");
+        assert_eq!(env.as_bytes().last(), Some(&b'\n'));
+    }
+
+    #[test]
+    fn test_env_multi_pair_ends_with_one_newline() {
+        let data = make_map(&[("A", "1"), ("B", "2\n\n")]);
+        let env = converters::env::convert(&data, &Default::default());
+        assert!(env.ends_with('\n'));
+        assert!(!env.ends_with("\n\n"));
+    }
+
+    // ── --select ──────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_select_top_level_key() {
+        let data = make_map(&[("password", "s3cret"), ("user", "admin")]);
+        assert_eq!(crate::detect::select_value(&data, "password"), Some(&json!("s3cret")));
+    }
+
+    #[test]
+    fn test_select_dotted_path() {
+        let Value::Object(data) = json!({"db": {"password": "s3cret", "hosts": ["a", "b"]}}) else {
+            unreachable!()
+        };
+        assert_eq!(crate::detect::select_value(&data, "db.password"), Some(&json!("s3cret")));
+        assert_eq!(crate::detect::select_value(&data, "db.hosts.1"), Some(&json!("b")));
+    }
+
+    #[test]
+    fn test_select_prefers_literal_dotted_key() {
+        let data = make_map(&[("db.password", "literal")]);
+        assert_eq!(crate::detect::select_value(&data, "db.password"), Some(&json!("literal")));
+    }
+
+    #[test]
+    fn test_select_missing_key() {
+        let data = make_map(&[("user", "admin")]);
+        assert!(crate::detect::select_value(&data, "password").is_none());
+        assert!(crate::detect::select_value(&data, "user.name").is_none());
+    }
+
+    // ── Local secret cache ────────────────────────────────────────────────────
+
+    #[test]
+    fn test_cache_hit_within_ttl() {
+        use crate::cache::{Cache, SecretValue};
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path().to_path_buf(), Duration::from_secs(60));
+        let value = SecretValue { secret_string: Some("KEY=val".to_string()), secret_binary: None, version_id: None };
+        let t0 = UNIX_EPOCH + Duration::from_secs(1_000);
+
+        cache.put("app", "AWSCURRENT", &value, t0).unwrap();
+        assert_eq!(cache.get("app", "AWSCURRENT", t0 + Duration::from_secs(59)), Some(value));
+        assert_eq!(cache.get("app", "AWSPREVIOUS", t0), None);
+    }
+
+    #[test]
+    fn test_cache_expires_after_ttl() {
+        use crate::cache::{Cache, SecretValue};
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path().to_path_buf(), Duration::from_secs(60));
+        let value = SecretValue { secret_string: Some("KEY=val".to_string()), secret_binary: None, version_id: None };
+        let t0 = UNIX_EPOCH + Duration::from_secs(1_000);
+
+        cache.put("app", "AWSCURRENT", &value, t0).unwrap();
+        assert_eq!(cache.get("app", "AWSCURRENT", t0 + Duration::from_secs(60)), None);
+    }
+
+    #[test]
+    fn test_cache_entries_are_scoped_by_region_and_profile() {
+        use crate::cache::{Cache, SecretValue};
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let dir = tempfile::tempdir().unwrap();
+        let cache = |region, profile| {
+            Cache::new(dir.path().to_path_buf(), Duration::from_secs(60)).scoped(Some(region), profile)
+        };
+        let value = SecretValue { secret_string: Some("KEY=eu".to_string()), secret_binary: None, version_id: None };
+        let t0 = UNIX_EPOCH + Duration::from_secs(1_000);
+
+        cache("eu-west-1", None).put("app", "AWSCURRENT", &value, t0).unwrap();
+        assert_eq!(cache("eu-west-1", None).get("app", "AWSCURRENT", t0), Some(value));
+        assert_eq!(cache("us-east-1", None).get("app", "AWSCURRENT", t0), None);
+        assert_eq!(cache("eu-west-1", Some("prod")).get("app", "AWSCURRENT", t0), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_cache_entries_written_with_0600_permissions() {
+        use crate::cache::{Cache, SecretValue};
+        use std::os::unix::fs::PermissionsExt;
+        use std::time::{Duration, SystemTime};
+
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path().join("cache"), Duration::from_secs(60));
+        let value = SecretValue { secret_string: Some("x".to_string()), secret_binary: None, version_id: None };
+        cache.put("app", "AWSCURRENT", &value, SystemTime::now()).unwrap();
+
+        for entry in std::fs::read_dir(dir.path().join("cache")).unwrap() {
+            let mode = entry.unwrap().metadata().unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    // ── --quiet ───────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_quiet_is_global_flag() {
+        use clap::Parser;
+        let cli = crate::Cli::try_parse_from(["sm2env", "list", "-q"]).unwrap();
+        assert!(cli.quiet);
+        let cli = crate::Cli::try_parse_from(["sm2env", "--quiet", "get", "app"]).unwrap();
+        assert!(cli.quiet);
+        let cli = crate::Cli::try_parse_from(["sm2env", "get", "app"]).unwrap();
+        assert!(!cli.quiet);
+    }
+
+    #[test]
+    fn test_verbose_flag_is_global() {
+        use clap::Parser;
+        let cli = crate::Cli::try_parse_from(["sm2env", "list", "--verbose"]).unwrap();
+        assert!(cli.verbose);
+        let cli = crate::Cli::try_parse_from(["sm2env", "-v", "get", "app"]).unwrap();
+        assert!(cli.verbose);
+    }
+
+    // ── --file - ──────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_file_dash_routes_to_stdout() {
+        use crate::output::OutputTarget;
+
+        for format in [OutputFormat::Env, OutputFormat::Json, OutputFormat::Yaml, OutputFormat::Csv] {
+            assert!(OutputTarget { file: Some("-"), ..Default::default() }.resolve(Some(&format)).is_none());
+        }
+        assert!(OutputTarget { file: Some("-"), ..Default::default() }.resolve(None).is_none());
+        assert!(!Path::new("-").exists());
+    }
+
+    #[test]
+    fn test_output_destination_defaults() {
+        use crate::output::OutputTarget;
+
+        assert_eq!(
+            OutputTarget::default().resolve(Some(&OutputFormat::Json)),
+            Some(std::path::PathBuf::from("secret.json"))
+        );
+        assert_eq!(
+            OutputTarget { output_dir: Some("out"), ..Default::default() }.resolve(Some(&OutputFormat::Env)),
+            Some(Path::new("out").join(".env"))
+        );
+        assert!(OutputTarget::default().resolve(Some(&OutputFormat::Stdout)).is_none());
+        assert_eq!(
+            OutputTarget { file: Some("custom.env"), ..Default::default() }.resolve(Some(&OutputFormat::Stdout)),
+            Some(std::path::PathBuf::from("custom.env"))
+        );
+    }
+
+    // ── INI output ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_ini_sections_from_nested_objects() {
+        let Value::Object(data) = json!({
+            "app_name": "demo",
+            "database": {"host": "db.local", "port": 5432},
+            "cache": {"url": "redis://cache"}
+        }) else {
+            unreachable!()
+        };
+        let ini = converters::ini::convert(&data, Default::default());
+        assert_eq!(
+            ini,
+            "[DEFAULT]\napp_name=demo\n\n[database]\nhost=db.local\nport=5432\n\n[cache]\nurl=redis://cache\n"
+        );
+    }
+
+    #[test]
+    fn test_ini_plain_text_under_default_section() {
+        let map = secret_to_map(detect_secret_format("just a plain string"));
+        let ini = converters::ini::convert(&map, Default::default());
+        assert_eq!(ini, "[DEFAULT]\nSECRET_VALUE=just a plain string\n");
+    }
+
+    #[test]
+    fn test_ini_escapes_special_values() {
+        assert_eq!(converters::ini::escape_ini(&json!("a;b")), "\"a;b\"");
+        assert_eq!(converters::ini::escape_ini(&json!("line1\nline2")), "\"line1\\nline2\"");
+        assert_eq!(converters::ini::escape_ini(&json!(" padded")), "\" padded\"");
+        assert_eq!(converters::ini::escape_ini(&json!("plain")), "plain");
+    }
+
+    // ── Env key validation ────────────────────────────────────────────────────
+
+    #[test]
+    fn test_valid_env_keys() {
+        use crate::converters::env::is_valid_env_key;
+        assert!(is_valid_env_key("DB_HOST"));
+        assert!(is_valid_env_key("_private"));
+        assert!(is_valid_env_key("a1"));
+    }
+
+    #[test]
+    fn test_invalid_env_keys() {
+        use crate::converters::env::is_valid_env_key;
+        assert!(!is_valid_env_key("my key"));
+        assert!(!is_valid_env_key("1password"));
+        assert!(!is_valid_env_key("db-host"));
+        assert!(!is_valid_env_key(""));
+    }
+
+    #[test]
+    fn test_invalid_env_keys_listed() {
+        let data = make_map(&[("GOOD", "1"), ("my key", "2"), ("1password", "3")]);
+        assert_eq!(converters::env::invalid_env_keys(&data), vec!["my key", "1password"]);
+    }
+
+    // ── get --all ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_sanitize_filename() {
+        use crate::output::sanitize_filename;
+        assert_eq!(sanitize_filename("prod/db"), "prod_db");
+        assert_eq!(sanitize_filename("app:v1 key"), "app_v1_key");
+        assert_eq!(sanitize_filename("plain-name_1.2"), "plain-name_1.2");
+    }
+
+    #[test]
+    fn test_output_destination_with_name_stem() {
+        use crate::output::OutputTarget;
+
+        assert_eq!(
+            OutputTarget { name_stem: Some("prod_db"), ..Default::default() }.resolve(Some(&OutputFormat::Env)),
+            Some(std::path::PathBuf::from("prod_db.env"))
+        );
+        assert_eq!(
+            OutputTarget { output_dir: Some("out"), name_stem: Some("prod_db"), ..Default::default() }
+                .resolve(Some(&OutputFormat::Json)),
+            Some(Path::new("out").join("prod_db.json"))
+        );
+    }
+
+    #[test]
+    fn test_get_all_does_not_require_names() {
+        use clap::Parser;
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "--all", "--filter", "prod/"]).is_ok());
+        assert!(crate::Cli::try_parse_from(["sm2env", "get"]).is_err());
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "app", "--filter", "prod"]).is_err());
+    }
+
+    #[test]
+    fn test_batch_file_name_list() {
+        let text = "# prod secrets\napp/db\n\n  app/api  \n# app/old\n";
+        assert_eq!(crate::parse_name_list(text), vec!["app/db", "app/api"]);
+    }
+
+    #[test]
+    fn test_batch_file_conflicts() {
+        use clap::Parser;
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "--batch-file", "secrets.txt", "--output-dir", "env"]).is_ok());
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "--batch-file", "secrets.txt", "--all"]).is_err());
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "app", "--batch-file", "secrets.txt"]).is_err());
+    }
+
+    // ── --timeout ─────────────────────────────────────────────────────────────
+
+    #[tokio::test]
+    async fn test_timeout_expires_on_stalled_operation() {
+        let stalled = std::future::pending::<Result<(), Box<dyn std::error::Error>>>();
+        let err = crate::with_timeout(Some(0), stalled).await.unwrap_err();
+        assert_eq!(err.to_string(), "Operation timed out after 0 seconds");
+    }
+
+    #[tokio::test]
+    async fn test_no_timeout_passes_result_through() {
+        let done = async { Ok::<_, Box<dyn std::error::Error>>(42) };
+        assert_eq!(crate::with_timeout(None, done).await.unwrap(), 42);
+    }
+
+    // ── Secret name from stdin ────────────────────────────────────────────────
+
+    #[test]
+    fn test_dash_reads_name_from_stdin() {
+        let names = vec!["-".to_string()];
+        let resolved = crate::resolve_secret_names(&names, false, "my/secret\n".as_bytes()).unwrap();
+        assert_eq!(resolved, vec!["my/secret".to_string()]);
+    }
+
+    #[test]
+    fn test_stdin_flag_reads_trimmed_name() {
+        let resolved = crate::resolve_secret_names(&[], true, "\n  app/db  \n".as_bytes()).unwrap();
+        assert_eq!(resolved, vec!["app/db".to_string()]);
+    }
+
+    #[test]
+    fn test_names_without_dash_do_not_read_stdin() {
+        let names = vec!["a".to_string(), "b".to_string()];
+        let resolved = crate::resolve_secret_names(&names, false, "ignored".as_bytes()).unwrap();
+        assert_eq!(resolved, names);
+    }
+
+    #[test]
+    fn test_empty_stdin_errors() {
+        let err = crate::resolve_secret_names(&[], true, "".as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("No secret name provided on stdin"));
+    }
+
+    // ── Docker Compose environment ────────────────────────────────────────────
+
+    #[test]
+    fn test_compose_env_list_form() {
+        let data = make_map(&[("DB_HOST", "db"), ("PRICE", "$5")]);
+        let out = converters::compose::convert(&data, false).unwrap();
+        let parsed: Vec<String> = serde_yml::from_str(&out).unwrap();
+        assert_eq!(parsed, vec!["DB_HOST=db".to_string(), "PRICE=$$5".to_string()]);
+    }
+
+    #[test]
+    fn test_compose_env_map_form_stringifies_values() {
+        let Value::Object(data) = json!({"PORT": 5432, "DEBUG": true}) else { unreachable!() };
+        let opts = converters::ConvertOptions { compose_map: true, ..Default::default() };
+        let out = converters::convert_to_format(&data, &OutputFormat::ComposeEnv, &opts).unwrap();
+        let parsed: Map<String, Value> = serde_yml::from_str(&out).unwrap();
+        assert_eq!(parsed["PORT"], "5432");
+        assert_eq!(parsed["DEBUG"], "true");
+    }
+
+    // ── --concat ──────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_concat_inserts_missing_newline() {
+        use crate::output::append_output;
+
+        let temp = NamedTempFile::new_in(".").unwrap();
+        std::fs::write(temp.path(), "KEY=val").unwrap();
+        append_output("KEY2=val2\n", temp.path()).unwrap();
+        assert_eq!(std::fs::read_to_string(temp.path()).unwrap(), "KEY=val\nKEY2=val2\n");
+    }
+
+    #[test]
+    fn test_concat_keeps_existing_lines_and_duplicates() {
+        use crate::output::append_output;
+
+        let temp = NamedTempFile::new_in(".").unwrap();
+        std::fs::write(temp.path(), "KEY=old\n").unwrap();
+        append_output("KEY=new\n", temp.path()).unwrap();
+        assert_eq!(std::fs::read_to_string(temp.path()).unwrap(), "KEY=old\nKEY=new\n");
+    }
+
+    #[test]
+    fn test_concat_creates_missing_file() {
+        use crate::output::append_output;
+
+        let dir = tempfile::tempdir_in(".").unwrap();
+        let path = dir.path().join("new.env");
+        append_output("A=1\n", &path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "A=1\n");
+    }
+
+    // ── Color ─────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_color_allowed_by_default() {
+        assert!(crate::ui::color_allowed(false, None));
+        assert!(crate::ui::color_allowed(false, Some("".into())));
+    }
+
+    #[test]
+    fn test_no_color_env_and_flag_disable_color() {
+        assert!(!crate::ui::color_allowed(false, Some("1".into())));
+        assert!(!crate::ui::color_allowed(true, None));
+    }
+
+    // ── Rotate command ────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_rotate_args() {
+        use clap::Parser;
+        let cli = crate::Cli::try_parse_from([
+            "sm2env", "rotate", "app", "--rotation-lambda-arn", "arn:aws:lambda:us-east-1:1:function:rot", "--immediate",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(crate::Commands::Rotate { secret_name, rotation_lambda_arn, immediate, .. }) => {
+                assert_eq!(secret_name, "app");
+                assert!(rotation_lambda_arn.is_some());
+                assert!(immediate);
+            }
+            _ => panic!("Expected rotate command"),
+        }
+    }
+
+    // ── --env-suffix ──────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_env_suffix_default_filename() {
+        use crate::output::OutputTarget;
+
+        let target = OutputTarget { env_suffix: Some("local"), ..Default::default() };
+        assert_eq!(target.resolve(Some(&OutputFormat::Env)), Some(std::path::PathBuf::from(".env.local")));
+        // Other formats keep their default names
+        assert_eq!(target.resolve(Some(&OutputFormat::Json)), Some(std::path::PathBuf::from("secret.json")));
+        // An explicit --file wins
+        let target = OutputTarget { file: Some("app.env"), env_suffix: Some("local"), ..Default::default() };
+        assert_eq!(target.resolve(Some(&OutputFormat::Env)), Some(std::path::PathBuf::from("app.env")));
+    }
+
+    // ── Library API ───────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_library_format_secret() {
+        let opts = converters::ConvertOptions::default();
+        let env = sm2env::format_secret(r#"{"A":"1","B":"2"}"#, &OutputFormat::Env, &opts).unwrap();
+        assert_eq!(env, "A=1\nB=2\n");
+        let env = sm2env::format_secret(r#"["x","y"]"#, &OutputFormat::Env, &opts).unwrap();
+        assert_eq!(env, "SECRET_0=x\nSECRET_1=y\n");
+        let json = sm2env::convert_env_to_json("A=1\n").unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), json!({"A": "1"}));
+    }
+
+    #[test]
+    fn test_library_key_transforms() {
+        let data = make_map(&[("A", "1"), ("B", "2")]);
+        let (filtered, missing) = sm2env::filter_keys(data, &["B", "C"]);
+        assert_eq!(filtered, make_map(&[("B", "2")]));
+        assert_eq!(missing, vec!["C".to_string()]);
+
+        let prefixed = sm2env::prefix_keys(filtered, "APP_");
+        assert_eq!(prefixed, make_map(&[("APP_B", "2")]));
+
+        assert_eq!(sm2env::format_value(&json!("raw")), "raw");
+        assert_eq!(sm2env::format_value(&json!({"a": 1})), r#"{"a":1}"#);
+    }
+
+    // ── Shell completions ─────────────────────────────────────────────────────
+
+    #[test]
+    fn test_completions_generated_for_each_shell() {
+        use clap_complete::Shell;
+
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let mut out = Vec::new();
+            crate::write_completions(shell, &mut out);
+            let script = String::from_utf8(out).unwrap();
+            assert!(script.contains("sm2env"), "empty completions for {}", shell);
+        }
+    }
+
+    // ── --line-ending ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_crlf_line_endings() {
+        use crate::converters::LineEnding;
+
+        let data = make_map(&[("A", "1"), ("B", "2")]);
+        let crlf = converters::ConvertOptions { line_ending: LineEnding::Crlf, ..Default::default() };
+        assert_eq!(converters::env::convert(&data, &crlf), "A=1\r\nB=2\r\n");
+        assert_eq!(converters::env::convert(&data, &Default::default()), "A=1\nB=2\n");
+
+        let mut nested = make_map(&[("A", "1")]);
+        nested.insert("db".to_string(), json!({"host": "h"}));
+        let ini = converters::ini::convert(&nested, LineEnding::Crlf);
+        assert_eq!(ini, "[DEFAULT]\r\nA=1\r\n\r\n[db]\r\nhost=h\r\n");
+    }
+
+    // ── --json-compact ────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_json_compact() {
+        let opts = converters::ConvertOptions { json_compact: true, ..Default::default() };
+        let mut data = make_map(&[("A", "1")]);
+        data.insert("db".to_string(), json!({"host": "h"}));
+        let compact = converters::convert_to_format(&data, &OutputFormat::Json, &opts).unwrap();
+        assert_eq!(compact, r#"{"A":"1","db":{"host":"h"}}"#);
+        let array = converters::convert_array(&[json!("x"), json!(1)], &OutputFormat::Json, "SECRET", &opts).unwrap();
+        assert_eq!(array, r#"["x",1]"#);
+        // Pretty-printed by default
+        let pretty = converters::convert_to_format(&data, &OutputFormat::Json, &Default::default()).unwrap();
+        assert!(pretty.contains('\n'));
+    }
+
+    // ── --key-name ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_plain_text_custom_key_name() {
+        let map = crate::detect::plain_text_to_map("s3cr3t".to_string(), "DATABASE_PASSWORD", Default::default());
+        assert_eq!(map, make_map(&[("DATABASE_PASSWORD", "s3cr3t")]));
+        // KEY=value text keeps its own keys
+        let map = crate::detect::plain_text_to_map("A=1".to_string(), "DATABASE_PASSWORD", Default::default());
+        assert_eq!(map, make_map(&[("A", "1")]));
+    }
+
+    // ── Partial name suggestions ──────────────────────────────────────────────
+
+    #[test]
+    fn test_not_found_message_lists_candidates() {
+        let candidates = vec![
+            ("app/db-a1".to_string(), "arn:aws:secretsmanager:us-east-1:1:secret:app/db-a1-AbCdEf".to_string()),
+            ("app/db-b2".to_string(), "arn:aws:secretsmanager:us-east-1:2:secret:app/db-b2-GhIjKl".to_string()),
+        ];
+        let message = crate::not_found_message("app/db", &candidates);
+        assert!(message.starts_with("Secret 'app/db' not found."));
+        assert!(message.contains("\n  - app/db-a1 (arn:aws:secretsmanager:us-east-1:1:secret:app/db-a1-AbCdEf)"));
+        assert!(message.contains("\n  - app/db-b2 (arn:aws:secretsmanager:us-east-1:2:secret:app/db-b2-GhIjKl)"));
+    }
+
+    // ── list sorting ──────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_sort_secrets() {
+        use aws_sdk_secretsmanager::primitives::DateTime;
+        use aws_sdk_secretsmanager::types::SecretListEntry;
+
+        let entry = |name: &str, created: i64, changed: i64| {
+            SecretListEntry::builder()
+                .name(name)
+                .created_date(DateTime::from_secs(created))
+                .last_changed_date(DateTime::from_secs(changed))
+                .build()
+        };
+        let mut entries = vec![entry("b", 1, 30), entry("c", 3, 10), entry("a", 2, 20)];
+        let names = |entries: &[SecretListEntry]| entries.iter().map(|e| e.name().unwrap().to_string()).collect::<Vec<_>>();
+
+        crate::sort_secrets(&mut entries, crate::SortBy::Name);
+        assert_eq!(names(&entries), ["a", "b", "c"]);
+        crate::sort_secrets(&mut entries, crate::SortBy::Created);
+        assert_eq!(names(&entries), ["b", "a", "c"]);
+        crate::sort_secrets(&mut entries, crate::SortBy::Changed);
+        assert_eq!(names(&entries), ["c", "a", "b"]);
+    }
+
+    #[test]
+    fn test_no_sort_conflicts_with_sort_by() {
+        use clap::Parser;
+        assert!(crate::Cli::try_parse_from(["sm2env", "list", "--no-sort"]).is_ok());
+        assert!(crate::Cli::try_parse_from(["sm2env", "list", "--no-sort", "--sort-by", "created"]).is_err());
+    }
+
+    // ── direnv .envrc ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_direnv_output() {
+        let data = make_map(&[("A", "plain"), ("B", "it's $HOME"), ("C", "")]);
+        let out = converters::convert_to_format(&data, &OutputFormat::Direnv, &Default::default()).unwrap();
+        assert_eq!(
+            out,
+            "# managed by sm2env\nexport A=plain\nexport B='it'\\''s $HOME'\nexport C=''\n"
+        );
+        assert_eq!(OutputFormat::Direnv.default_filename(), ".envrc");
+    }
+
+    // ── --include / --exclude ─────────────────────────────────────────────────
+
+    #[test]
+    fn test_include_exclude_keys() {
+        let data = make_map(&[("DB_HOST", "h"), ("DB_PASS", "p"), ("API_KEY", "k"), ("SECRET_KEY", "s")]);
+        let globs = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let out = sm2env::include_exclude_keys(data.clone(), &globs(&["DB_*"]), &[]).unwrap();
+        assert_eq!(out.keys().collect::<Vec<_>>(), ["DB_HOST", "DB_PASS"]);
+
+        let out = sm2env::include_exclude_keys(data.clone(), &[], &globs(&["SECRET_KEY"])).unwrap();
+        assert_eq!(out.keys().collect::<Vec<_>>(), ["DB_HOST", "DB_PASS", "API_KEY"]);
+
+        let out = sm2env::include_exclude_keys(data.clone(), &globs(&["DB_*", "*_KEY"]), &globs(&["*PASS", "SECRET_*"])).unwrap();
+        assert_eq!(out.keys().collect::<Vec<_>>(), ["DB_HOST", "API_KEY"]);
+
+        assert_eq!(sm2env::include_exclude_keys(data.clone(), &[], &[]).unwrap(), data);
+        assert!(sm2env::include_exclude_keys(data, &globs(&["[DB"]), &[]).is_err());
+    }
+
+    // ── Atomic writes ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_failed_write_leaves_original_intact() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".env");
+        std::fs::write(&path, "OLD=1\n").unwrap();
+
+        let result = crate::output::write_atomic_with(&path, |file| {
+            use std::io::Write;
+            file.write_all(b"NEW=")?;
+            Err(std::io::Error::other("killed mid-write"))
+        });
+        assert!(result.is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "OLD=1\n");
+        // No temp file is left behind
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        crate::output::write_secure(&path, "NEW=2\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "NEW=2\n");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    // ── list --tag ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_tag_filter() {
+        use aws_sdk_secretsmanager::types::{SecretListEntry, Tag};
+
+        let tag = crate::parse_tag_filter("env=prod").unwrap();
+        assert_eq!(tag.to_aws_filters().len(), 2);
+        let key_only = crate::parse_tag_filter("team").unwrap();
+        assert_eq!(key_only.to_aws_filters().len(), 1);
+        assert!(crate::parse_tag_filter("=prod").is_err());
+
+        let entry = SecretListEntry::builder()
+            .name("app")
+            .tags(Tag::builder().key("env").value("prod").build())
+            .tags(Tag::builder().key("team").value("web").build())
+            .build();
+        assert!(tag.matches(&entry));
+        assert!(key_only.matches(&entry));
+        // Key and value must belong to the same tag
+        assert!(!crate::parse_tag_filter("team=prod").unwrap().matches(&entry));
+    }
+
+    // ── --clipboard ───────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_clipboard_flag_conflicts() {
+        use clap::Parser;
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "app", "--select", "password", "--clipboard"]).is_ok());
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "app", "--clipboard", "--file", ".env"]).is_err());
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "app", "--clipboard", "--dry-run"]).is_err());
+    }
+
+    // ── --header ──────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_provenance_header() {
+        use sm2env::header::{format_timestamp, Header};
+        use std::time::{Duration, UNIX_EPOCH};
+
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(UNIX_EPOCH + Duration::from_secs(1_709_210_096)), "2024-02-29T12:34:56Z");
+
+        let names = vec!["app/db".to_string()];
+        let header = Header { secret_names: &names, generated_at: UNIX_EPOCH };
+        let expected = format!("generated by sm2env {} from secret app/db at 1970-01-01T00:00:00Z", env!("CARGO_PKG_VERSION"));
+        assert_eq!(header.text(), expected);
+
+        let env = header.prepend("A=1\n".to_string(), &OutputFormat::Env, Default::default());
+        assert_eq!(env, format!("# {}\nA=1\n", expected));
+        let ini = header.prepend("[DEFAULT]\n".to_string(), &OutputFormat::Ini, Default::default());
+        assert!(ini.starts_with("; generated by sm2env"));
+        // JSON has no comments; it gets a _sm2env_meta key instead
+        assert_eq!(header.prepend("{}".to_string(), &OutputFormat::Json, Default::default()), "{}");
+        assert_eq!(header.meta()["secrets"], json!(["app/db"]));
+    }
+
+    // ── KMS decryption errors ────────────────────────────────────────────────
+
+    #[test]
+    fn test_kms_decrypt_failure_detection() {
+        use crate::is_kms_decrypt_failure;
+        use sm2env::errors::SmError;
+
+        assert!(is_kms_decrypt_failure(true, None, None));
+        assert!(is_kms_decrypt_failure(
+            false,
+            Some("AccessDeniedException"),
+            Some("Access to KMS is not allowed"),
+        ));
+        // access denied on Secrets Manager itself is a different problem
+        assert!(!is_kms_decrypt_failure(
+            false,
+            Some("AccessDeniedException"),
+            Some("not authorized to perform: secretsmanager:GetSecretValue"),
+        ));
+        assert!(!is_kms_decrypt_failure(false, Some("ResourceNotFoundException"), None));
+
+        let err = SmError::KmsDecryptError("app/db".to_string());
+        assert!(err.to_string().contains("kms:Decrypt"));
+        assert_eq!(err.exit_code(), 3);
+        assert_eq!(SmError::AwsError("boom".to_string()).exit_code(), 1);
+    }
+
+    // ── --max-concurrency ────────────────────────────────────────────────────
+
+    #[tokio::test]
+    async fn test_run_bounded_limits_in_flight() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let items: Vec<usize> = (0..20).collect();
+        let results = crate::run_bounded(&items, 3, |&i| {
+            let (in_flight, peak) = (&in_flight, &peak);
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                i * 2
+            }
+        })
+        .await;
+
+        assert_eq!(results, (0..20).map(|i| i * 2).collect::<Vec<_>>());
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_max_concurrency_flag() {
+        use clap::Parser;
+        let cli = crate::Cli::try_parse_from(["sm2env", "get", "--all"]).unwrap();
+        assert!(matches!(cli.command, Some(crate::Commands::Get { max_concurrency: 8, .. })));
+        let cli = crate::Cli::try_parse_from(["sm2env", "get", "--all", "--max-concurrency", "2"]).unwrap();
+        assert!(matches!(cli.command, Some(crate::Commands::Get { max_concurrency: 2, .. })));
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "--all", "--max-concurrency", "0"]).is_err());
+    }
+
+    // ── Key order / --sort-keys ──────────────────────────────────────────────
+
+    #[test]
+    fn test_key_order_preserved_unless_sorted() {
+        let secret = r#"{"ZETA":"z","alpha":{"b":1,"a":2},"MID":"m"}"#;
+        let map = secret_to_map(detect_secret_format(secret));
+        assert_eq!(map.keys().collect::<Vec<_>>(), ["ZETA", "alpha", "MID"]);
+
+        let json = converters::json::convert(&map, true).unwrap();
+        assert_eq!(json, secret);
+        let env = converters::env::convert(&map, &Default::default());
+        assert!(env.starts_with("ZETA=z\n"));
+
+        let sorted = sm2env::sort_keys(map);
+        assert_eq!(
+            converters::json::convert(&sorted, true).unwrap(),
+            r#"{"MID":"m","ZETA":"z","alpha":{"a":2,"b":1}}"#
+        );
+    }
+
+    // ── --output keys ────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_keys_output_bare_names() {
+        let data = make_map(&[("DB_HOST", "h"), ("DB_PASS", "p")]);
+        let out = converters::convert_to_format(&data, &OutputFormat::Keys, &Default::default()).unwrap();
+        assert_eq!(out, "DB_HOST\nDB_PASS\n");
+        assert_eq!(crate::output::OutputTarget::default().resolve(Some(&OutputFormat::Keys)), None);
+    }
+
+    #[test]
+    fn test_keys_output_goes_to_files_per_secret() {
+        use crate::output::OutputTarget;
+        use std::path::PathBuf;
+
+        let in_dir = OutputTarget { output_dir: Some("out"), ..Default::default() };
+        assert_eq!(in_dir.resolve(Some(&OutputFormat::Keys)), Some(PathBuf::from("out/.env.example")));
+        let per_secret = OutputTarget { name_stem: Some("prod_db"), ..Default::default() };
+        assert_eq!(per_secret.resolve(Some(&OutputFormat::Keys)), Some(PathBuf::from("prod_db.env.example")));
+        let piped = OutputTarget { file: Some("-"), name_stem: Some("prod_db"), ..Default::default() };
+        assert_eq!(piped.resolve(Some(&OutputFormat::Keys)), None);
+    }
+
+    #[test]
+    fn test_keys_output_example_placeholders() {
+        let data = make_map(&[("DB_HOST", "h"), ("DB_PASS", "p")]);
+        let opts = converters::ConvertOptions { example: true, ..Default::default() };
+        let out = converters::convert_to_format(&data, &OutputFormat::Keys, &opts).unwrap();
+        assert_eq!(out, "DB_HOST=\nDB_PASS=\n");
+
+        use clap::Parser;
+        let cli = crate::Cli::try_parse_from(["sm2env", "get", "app", "--output", "keys", "--example"]).unwrap();
+        assert!(matches!(cli.command, Some(crate::Commands::Get { example: true, .. })));
+    }
+
+    // ── --keyring ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_keyring_payload() {
+        let data = make_map(&[("user", "admin"), ("pass", "s3cret")]);
+        assert_eq!(crate::keyring_payload(None, &data).unwrap(), r#"{"user":"admin","pass":"s3cret"}"#);
+        assert_eq!(
+            crate::keyring_payload(Some("just-a-token".to_string()), &Map::new()).unwrap(),
+            "just-a-token"
+        );
+
+        use clap::Parser;
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "app", "--keyring", "myservice"]).is_ok());
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "app", "--keyring", "svc", "--file", ".env"]).is_err());
+    }
+
+    // ── --escape-dollar ──────────────────────────────────────────────────────
+
+    #[test]
+    fn test_env_escape_dollar() {
+        let data = make_map(&[("PASSWORD", "pa$$word")]);
+        let plain = converters::env::convert(&data, &Default::default());
+        assert_eq!(plain, "PASSWORD=pa$$word\n");
+
+        let opts = converters::ConvertOptions { env_escape_dollar: true, ..Default::default() };
+        assert_eq!(converters::env::convert(&data, &opts), "PASSWORD=pa$$$$word\n");
+    }
+
+    // ── list --all-regions ───────────────────────────────────────────────────
+
+    #[test]
+    fn test_list_all_regions_flag() {
+        use clap::Parser;
+        let cli = crate::Cli::try_parse_from(["sm2env", "list", "--all-regions"]).unwrap();
+        assert!(matches!(cli.command, Some(crate::Commands::List { all_regions: true, .. })));
+        assert!(crate::Cli::try_parse_from(["sm2env", "list", "--all-regions", "--region", "us-east-1"]).is_err());
+
+        let regions = sm2env::aws_client::SECRETS_MANAGER_REGIONS;
+        assert!(regions.contains(&"us-east-1"));
+        let unique: std::collections::HashSet<_> = regions.iter().collect();
+        assert_eq!(unique.len(), regions.len());
+    }
+
+    // ── list --since ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_parse_since_durations() {
+        use crate::parse_since;
+        use std::time::Duration;
+
+        assert_eq!(parse_since("90m"), Ok(Duration::from_secs(90 * 60)));
+        assert_eq!(parse_since("24h"), Ok(Duration::from_secs(86_400)));
+        assert_eq!(parse_since("7d"), Ok(Duration::from_secs(7 * 86_400)));
+        assert_eq!(parse_since("2w"), Ok(Duration::from_secs(14 * 86_400)));
+        assert!(parse_since("24").is_err());
+        assert!(parse_since("h").is_err());
+        assert!(parse_since("3y").is_err());
+    }
+
+    #[test]
+    fn test_since_cutoff_comparison() {
+        use aws_sdk_secretsmanager::primitives::DateTime;
+        use aws_sdk_secretsmanager::types::SecretListEntry;
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let now = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let cutoff = crate::changed_cutoff(now, Duration::from_secs(3_600));
+        assert_eq!(cutoff, 996_400);
+
+        let changed_at = |secs: i64| {
+            SecretListEntry::builder()
+                .name("app")
+                .last_changed_date(DateTime::from_secs(secs))
+                .build()
+        };
+        assert!(crate::changed_since(&changed_at(999_000), cutoff));
+        assert!(crate::changed_since(&changed_at(996_400), cutoff));
+        assert!(!crate::changed_since(&changed_at(900_000), cutoff));
+        assert!(!crate::changed_since(&SecretListEntry::builder().name("app").build(), cutoff));
+    }
+
+    // ── diff ─────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_diff_maps_reports_added_removed_changed() {
+        use sm2env::diff::{diff_maps, KeyChange};
+
+        let remote = secret_to_map(detect_secret_format(r#"{"HOST":"db","PORT":5432,"OLD":"x","PASS":"a"}"#));
+        let local = secret_to_map(detect_secret_format("HOST=db\nPORT=5432\nPASS=b\nNEW=y\n"));
+        let changes = diff_maps(&remote, &local);
+        assert_eq!(
+            changes,
+            vec![
+                ("OLD".to_string(), KeyChange::Removed { value: "x".to_string() }),
+                ("PASS".to_string(), KeyChange::Changed { old: "a".to_string(), new: "b".to_string() }),
+                ("NEW".to_string(), KeyChange::Added { value: "y".to_string() }),
+            ]
+        );
+        assert!(diff_maps(&remote, &remote).is_empty());
+
+        assert_eq!(crate::diff_lines(&changes, false), ["- OLD", "~ PASS", "+ NEW"]);
+        assert_eq!(crate::diff_lines(&changes, true), ["- OLD=x", "~ PASS", "-   a", "+   b", "+ NEW=y"]);
+        assert_eq!(sm2env::errors::SmError::DriftDetected(3).exit_code(), 1);
+    }
+
+    // ── --trim-whitespace / --no-trim ────────────────────────────────────────
+
+    #[test]
+    fn test_trim_modes_with_significant_trailing_spaces() {
+        use crate::detect::{plain_text_to_map, trim_value, TrimMode};
+
+        // Default: KEY=value text is trimmed, JSON strings are kept as stored
+        let env = plain_text_to_map("TOKEN=abc  \n".to_string(), "SECRET_VALUE", TrimMode::KeyValueOnly);
+        assert_eq!(env["TOKEN"], "abc");
+        let Value::Object(json) = json!({"TOKEN": "abc  "}) else { unreachable!() };
+        assert_eq!(secret_to_map(SecretFormat::Json(json.clone()))["TOKEN"], "abc  ");
+
+        // --no-trim keeps the padding in KEY=value text too
+        let env = plain_text_to_map("TOKEN=abc  \n".to_string(), "SECRET_VALUE", TrimMode::None);
+        assert_eq!(env["TOKEN"], "abc  ");
+
+        // --trim-whitespace trims everywhere, including nested JSON and opaque text
+        let opaque = plain_text_to_map(" abc  ".to_string(), "SECRET_VALUE", TrimMode::All);
+        assert_eq!(opaque["SECRET_VALUE"], "abc");
+        let mut nested = json!({"TOKEN": "abc  ", "db": {"user": " admin "}, "n": 1});
+        trim_value(&mut nested);
+        assert_eq!(nested, json!({"TOKEN": "abc", "db": {"user": "admin"}, "n": 1}));
+
+        use clap::Parser;
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "app", "--trim-whitespace", "--no-trim"]).is_err());
+    }
+
+    // ── --interactive ────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_interactive_requires_terminal() {
+        use crate::picker::ensure_terminal;
+        assert!(ensure_terminal(true, true).is_ok());
+        assert!(ensure_terminal(false, true).is_err());
+        assert!(ensure_terminal(true, false).is_err());
+
+        use clap::Parser;
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "--interactive"]).is_ok());
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "-i", "--filter", "prod"]).is_ok());
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "app", "--interactive"]).is_err());
+        assert!(crate::Cli::try_parse_from(["sm2env", "list", "--interactive", "--all-regions"]).is_err());
+    }
+
+    // ── --wrap ───────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_wrap_json_document() {
+        let doc = sm2env::wrap_json(json!({"A": "1"}), "app", Some("v-123"));
+        assert_eq!(
+            converters::json::render(&Value::Object(doc), true).unwrap(),
+            r#"{"name":"app","version":"v-123","data":{"A":"1"}}"#
+        );
+        let doc = sm2env::wrap_json(json!(["x"]), "a,b", None);
+        assert_eq!(Value::Object(doc), json!({"name": "a,b", "data": ["x"]}));
+
+        use clap::Parser;
+        let cli = crate::Cli::try_parse_from(["sm2env", "get", "app", "--output", "json", "--wrap"]).unwrap();
+        assert!(matches!(cli.command, Some(crate::Commands::Get { wrap: true, .. })));
+    }
+
+    // ── --version ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_long_version_includes_build_info() {
+        use clap::CommandFactory;
+        let cmd = crate::Cli::command();
+        assert_eq!(cmd.get_version(), Some(env!("CARGO_PKG_VERSION")));
+        let long = cmd.get_long_version().unwrap();
+        assert!(long.starts_with(env!("CARGO_PKG_VERSION")));
+        assert!(long.contains(env!("SM2ENV_GIT_HASH")));
+        assert!(long.contains(env!("SM2ENV_BUILD_DATE")));
+    }
+
+    // ── Streamed JSON ────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_streamed_json_matches_rendered() {
+        let dir = tempfile::tempdir_in(".").unwrap();
+        let path = dir.path().join("secret.json");
+        let rel = path.strip_prefix(std::env::current_dir().unwrap()).unwrap_or(&path).to_path_buf();
+        let value = json!({"B": "2", "A": {"nested": [1, 2]}});
+        for compact in [false, true] {
+            crate::output::write_json(&rel, &value, compact).unwrap();
+            assert_eq!(
+                std::fs::read_to_string(&rel).unwrap(),
+                converters::json::render(&value, compact).unwrap()
+            );
+        }
+    }
+
+    // ── --refresh-cache ──────────────────────────────────────────────────────
+
+    #[test]
+    fn test_refreshing_cache_misses_but_still_stores() {
+        use crate::cache::{Cache, SecretValue};
+        use std::time::{Duration, SystemTime};
+
+        let dir = tempfile::tempdir().unwrap();
+        let value = |s: &str| SecretValue {
+            secret_string: Some(s.into()),
+            secret_binary: None,
+            version_id: None,
+        };
+        let t0 = SystemTime::now();
+        Cache::new(dir.path().to_path_buf(), Duration::from_secs(60))
+            .put("app", "AWSCURRENT", &value("old"), t0)
+            .unwrap();
+
+        let refreshing = Cache::new(dir.path().to_path_buf(), Duration::from_secs(60)).refreshing();
+        assert_eq!(refreshing.get("app", "AWSCURRENT", t0), None);
+        refreshing.put("app", "AWSCURRENT", &value("new"), t0).unwrap();
+
+        let cache = Cache::new(dir.path().to_path_buf(), Duration::from_secs(60));
+        assert_eq!(cache.get("app", "AWSCURRENT", t0), Some(value("new")));
+
+        use clap::Parser;
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "app", "--refresh-cache", "--no-cache"]).is_err());
+    }
+
+    // ── JSON primitive secrets ───────────────────────────────────────────────
+
+    #[test]
+    fn test_json_primitive_secrets_render_as_secret_value() {
+        use sm2env::detect::{plain_text_to_map, TrimMode, DEFAULT_PLAIN_KEY};
+
+        for (secret, expected) in [("true", "true"), ("42", "42"), (r#""quoted""#, "quoted")] {
+            let SecretFormat::PlainText(text) = detect_secret_format(secret) else {
+                panic!("{} should be plain text", secret);
+            };
+            let map = plain_text_to_map(text, DEFAULT_PLAIN_KEY, TrimMode::default());
+            assert_eq!(
+                converters::convert_to_format(&map, &OutputFormat::Env, &Default::default()).unwrap(),
+                format!("SECRET_VALUE={}\n", expected)
+            );
+        }
+    }
+
+    // ── --uppercase-keys / --report-keys ─────────────────────────────────────
+
+    #[test]
+    fn test_normalize_env_key() {
+        use sm2env::converters::env::normalize_env_key;
+
+        assert_eq!(normalize_env_key("api-key").as_deref(), Some("API_KEY"));
+        assert_eq!(normalize_env_key("db.host").as_deref(), Some("DB_HOST"));
+        assert_eq!(normalize_env_key("2fa").as_deref(), Some("_2FA"));
+        assert_eq!(normalize_env_key("TOKEN").as_deref(), Some("TOKEN"));
+        assert_eq!(normalize_env_key("--"), None);
+    }
+
+    #[test]
+    fn test_normalize_env_keys_reports_renames_and_drops() {
+        use sm2env::converters::env::{normalize_env_keys, KeyReport};
+
+        let data = json!({"TOKEN": "t", "api-key": "a", "api_key": "b", "..": "c"});
+        let (map, report) = normalize_env_keys(data.as_object().unwrap().clone());
+        assert_eq!(Value::Object(map), json!({"TOKEN": "t", "API_KEY": "a"}));
+        assert_eq!(
+            report,
+            KeyReport {
+                renamed: vec![("api-key".into(), "API_KEY".into())],
+                dropped: vec!["api_key".into(), "..".into()],
+            }
+        );
+
+        use clap::Parser;
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "app", "--report-keys"]).is_err());
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "app", "--uppercase-keys", "--report-keys"]).is_ok());
+    }
+
+    // ── --merge-into / --on-conflict ─────────────────────────────────────────
+
+    #[test]
+    fn test_check_conflicts() {
+        use crate::{check_conflicts, OnConflict};
+
+        let merged = json!({"A": "1", "B": "2"});
+        let merged = merged.as_object().unwrap();
+        let fresh = json!({"C": "3"});
+        let clashing = json!({"B": "x", "C": "3"});
+
+        assert!(check_conflicts(merged, fresh.as_object().unwrap(), "s2", OnConflict::Error).is_ok());
+        assert!(check_conflicts(merged, clashing.as_object().unwrap(), "s2", OnConflict::Warn).is_ok());
+        let err = check_conflicts(merged, clashing.as_object().unwrap(), "s2", OnConflict::Error).unwrap_err();
+        assert!(err.to_string().contains("'s2'") && err.to_string().contains("B"));
+    }
+
+    #[test]
+    fn test_merge_into_parsing() {
+        use clap::Parser;
+
+        let cli = crate::Cli::try_parse_from(["sm2env", "get", "a", "b", "--merge-into", "all.json"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(crate::Commands::Get { merge_into: Some(ref p), on_conflict: None, .. }) if p == "all.json"
+        ));
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "--all", "--merge-into", "x.json", "--on-conflict", "error"]).is_ok());
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "a", "--merge-into", "x.json", "--file", "y"]).is_err());
+    }
+
+    // ── --url-encode ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_url_encode_env_values() {
+        let data = json!({"DB_URL": "postgres://user:p@ss word@db/app", "NAME": "a-b.c_d~e"});
+        let opts = converters::ConvertOptions {
+            env_url_encode: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            converters::env::convert(data.as_object().unwrap(), &opts),
+            "DB_URL=postgres%3A%2F%2Fuser%3Ap%40ss%20word%40db%2Fapp\nNAME=a-b.c_d~e\n"
+        );
+        assert_eq!(
+            converters::env::convert(data.as_object().unwrap(), &Default::default()),
+            "DB_URL=postgres://user:p@ss word@db/app\nNAME=a-b.c_d~e\n"
+        );
+    }
+
+    // ── --with-kms-info ──────────────────────────────────────────────────────
+
+    #[test]
+    fn test_kms_key_label() {
+        let arn = "arn:aws:kms:us-east-1:123456789012:key/abcd";
+        assert_eq!(crate::kms_key_label(Some(arn)), arn);
+        assert_eq!(crate::kms_key_label(None), "aws/secretsmanager (AWS managed key)");
+    }
+
+    // ── FIFO targets ─────────────────────────────────────────────────────────
+
+    #[cfg(unix)]
+    fn make_fifo(path: &std::path::Path) {
+        use std::os::unix::ffi::OsStrExt;
+
+        let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_write_fifo_without_reader_times_out() {
+        use crate::output::{is_fifo, write_fifo};
+
+        let dir = tempfile::tempdir_in(".").unwrap();
+        let path = dir.path().join("pipe");
+        make_fifo(&path);
+        assert!(is_fifo(&path));
+
+        let err = write_fifo(&path, b"A=1\n", std::time::Duration::from_millis(100))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("No reader"));
+        assert!(is_fifo(&path));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_global_timeout_cancels_fifo_wait() {
+        use crate::output::write_fifo;
+
+        let dir = tempfile::tempdir_in(".").unwrap();
+        let path = dir.path().join("pipe");
+        make_fifo(&path);
+
+        let started = std::time::Instant::now();
+        let write = async {
+            write_fifo(&path, b"A=1\n", std::time::Duration::from_secs(10)).await?;
+            Ok(())
+        };
+        let err = crate::with_timeout(Some(0), write).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<sm2env::errors::SmError>(),
+            Some(sm2env::errors::SmError::TimeoutError(0))
+        ));
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_write_fifo_delivers_content_to_reader() {
+        use crate::output::{is_fifo, write_fifo};
+        use std::io::Read;
+
+        let dir = tempfile::tempdir_in(".").unwrap();
+        let path = dir.path().join("pipe");
+        make_fifo(&path);
+
+        let reader_path = path.clone();
+        let reader = std::thread::spawn(move || {
+            let mut content = String::new();
+            std::fs::File::open(reader_path).unwrap().read_to_string(&mut content).unwrap();
+            content
+        });
+        write_fifo(&path, b"A=1\n", std::time::Duration::from_secs(5)).await.unwrap();
+        assert_eq!(reader.join().unwrap(), "A=1\n");
+        assert!(is_fifo(&path));
+    }
+
+    // ── --schema ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_schema_validation() {
+        use sm2env::schema::{load, validate};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("schema.json");
+        std::fs::write(
+            &path,
+            r#"{"type": "object", "required": ["DB_HOST", "DB_PORT"],
+                "properties": {"DB_PORT": {"type": "integer"}}}"#,
+        )
+        .unwrap();
+        let validator = load(&path).unwrap();
+
+        let good = detect_secret_format(r#"{"DB_HOST": "db", "DB_PORT": 5432}"#);
+        assert!(validate(&validator, &good, "app").is_ok());
+
+        let bad = detect_secret_format(r#"{"DB_PORT": "5432"}"#);
+        let err = validate(&validator, &bad, "app").unwrap_err().to_string();
+        assert!(err.contains("secret 'app' has 2 violation(s)"), "{}", err);
+        assert!(err.contains("/DB_PORT: "), "{}", err);
+        assert!(err.contains("(root): "), "{}", err);
+
+        let plain = detect_secret_format("DB_HOST=db");
+        assert!(matches!(
+            validate(&validator, &plain, "app"),
+            Err(sm2env::errors::SmError::SchemaError(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_schema_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("schema.json");
+        std::fs::write(&path, r#"{"type": "no-such-type"}"#).unwrap();
+        assert!(sm2env::schema::load(&path).is_err());
+    }
+
+    // ── --bool-style ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_bool_style_env_output() {
+        use converters::{BoolStyle, ConvertOptions};
+
+        let data = json!({"DEBUG": true, "CACHE": false, "NAME": "true", "PORT": 8080});
+        let data = data.as_object().unwrap();
+        for (style, expected) in [
+            (BoolStyle::TrueFalse, "DEBUG=true\nCACHE=false\n"),
+            (BoolStyle::OneZero, "DEBUG=1\nCACHE=0\n"),
+            (BoolStyle::YesNo, "DEBUG=yes\nCACHE=no\n"),
+        ] {
+            let opts = ConvertOptions {
+                bool_style: style,
+                ..Default::default()
+            };
+            let out = converters::env::convert(data, &opts);
+            assert_eq!(out, format!("{}NAME=true\nPORT=8080\n", expected), "{:?}", style);
+        }
+
+        let opts = ConvertOptions {
+            bool_style: BoolStyle::YesNo,
+            ..Default::default()
+        };
+        let out = converters::convert_to_format(data, &OutputFormat::Direnv, &opts).unwrap();
+        assert!(out.contains("export DEBUG=yes\nexport CACHE=no\n"));
+    }
+
+    // ── --export-prefix ──────────────────────────────────────────────────────
+
+    #[test]
+    fn test_export_prefix_on_every_env_line() {
+        let data = json!({"DB_HOST": "localhost", "DB_PASS": "p w"});
+        let opts = converters::ConvertOptions {
+            env_export_prefix: true,
+            ..Default::default()
+        };
+        let out = converters::env::convert(data.as_object().unwrap(), &opts);
+        assert_eq!(out, "export DB_HOST=localhost\nexport DB_PASS=p w\n");
+        assert!(out.lines().all(|line| line.starts_with("export ")));
+        assert_eq!(Value::Object(crate::detect::parse_env_vars(&out)), data);
+    }
+
+    // ── list --count ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_list_count_flag() {
+        use clap::Parser;
+
+        let cli = crate::Cli::try_parse_from(["sm2env", "list", "--count", "--filter", "prod"]).unwrap();
+        assert!(matches!(cli.command, Some(crate::Commands::List { count: true, .. })));
+        assert!(crate::Cli::try_parse_from(["sm2env", "list", "--count", "--interactive"]).is_err());
+    }
+
+    // ── Region from ARN ──────────────────────────────────────────────────────
+
+    #[test]
+    fn test_region_from_arn() {
+        use sm2env::aws_client::region_from_arn;
+
+        assert_eq!(
+            region_from_arn("arn:aws:secretsmanager:eu-west-1:123456789012:secret:foo-AbCdEf"),
+            Some("eu-west-1")
+        );
+        assert_eq!(
+            region_from_arn("arn:aws-cn:secretsmanager:cn-north-1:123456789012:secret:foo"),
+            Some("cn-north-1")
+        );
+        assert_eq!(region_from_arn("prod/db"), None);
+        assert_eq!(region_from_arn("arn:aws:kms:eu-west-1:123456789012:key/abc"), None);
+        assert_eq!(region_from_arn("arn:aws:secretsmanager::123456789012:secret:foo"), None);
+    }
+
+    #[test]
+    fn test_common_region_rejects_secrets_in_different_regions() {
+        use sm2env::aws_client::common_region;
+
+        let eu = "arn:aws:secretsmanager:eu-west-1:123456789012:secret:a-AbCdEf".to_string();
+        let us = "arn:aws:secretsmanager:us-east-1:123456789012:secret:b-AbCdEf".to_string();
+        let err = common_region(&[eu.clone(), us.clone()], None).unwrap_err();
+        assert!(err.to_string().contains("eu-west-1: ") && err.to_string().contains("us-east-1: "), "{}", err);
+
+        // A plain name lives in the default region
+        assert!(common_region(&[eu.clone(), "app".to_string()], Some("us-east-1")).is_err());
+        assert_eq!(common_region(&[us.clone(), "app".to_string()], Some("us-east-1")).unwrap(), Some("us-east-1"));
+        assert_eq!(common_region(std::slice::from_ref(&eu), Some("us-east-1")).unwrap(), Some("eu-west-1"));
+        assert_eq!(common_region(&["app".to_string()], None).unwrap(), None);
+    }
+
+    // ── env --example ────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_env_example_placeholders_hide_values() {
+        let data = make_map(&[("DATABASE_URL", "postgres://u:hunter2@db/app"), ("API_KEY", "sk-live-123")]);
+        let opts = converters::ConvertOptions {
+            example: true,
+            ..Default::default()
+        };
+        let out = converters::convert_to_format(&data, &OutputFormat::Env, &opts).unwrap();
+        assert_eq!(out, "DATABASE_URL=<database_url>\nAPI_KEY=<api_key>\n");
+        for value in data.values() {
+            assert!(!out.contains(value.as_str().unwrap()));
+        }
+        assert!(!out.contains("hunter2"));
+    }
+
+    // ── --error-format json ──────────────────────────────────────────────────
+
+    #[test]
+    fn test_error_json() {
+        use sm2env::errors::SmError;
+
+        let err = SmError::NotFound {
+            secret: "prod/db".into(),
+            message: "Secret not found".into(),
+        };
+        assert_eq!(
+            crate::error_json(&err),
+            json!({"error": "AWS error: Secret not found", "code": "NotFound", "secret": "prod/db"})
+        );
+
+        let err = SmError::FormatError("bad".into());
+        assert_eq!(crate::error_json(&err), json!({"error": "Format error: bad", "code": "FormatError"}));
+
+        let err = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+        assert_eq!(crate::error_json(&err)["code"], "IoError");
+
+        use clap::Parser;
+        let cli = crate::Cli::try_parse_from(["sm2env", "get", "app", "--error-format", "json"]).unwrap();
+        assert_eq!(cli.error_format, crate::ErrorFormat::Json);
+    }
+
+    // ── --detect-type ────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_sniff_binary_type() {
+        use sm2env::detect::sniff_binary_type;
+
+        let png = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0x0D];
+        assert_eq!(sniff_binary_type(&png), Some(("png", "image/png")));
+        assert_eq!(sniff_binary_type(b"%PDF-1.7\n").map(|t| t.0), Some("pdf"));
+        assert_eq!(
+            sniff_binary_type(b"-----BEGIN CERTIFICATE-----\nMIIB"),
+            Some(("pem", "application/x-pem-file"))
+        );
+        let p12 = [0x30, 0x82, 0x0A, 0x1B, 0x02, 0x01, 0x03, 0x30];
+        assert_eq!(sniff_binary_type(&p12), Some(("p12", "application/x-pkcs12")));
+        assert_eq!(sniff_binary_type(&[0x30, 0x03, 0x02, 0x01, 0x01]), None);
+        assert_eq!(sniff_binary_type(b"just some text"), None);
+        assert_eq!(sniff_binary_type(&[]), None);
+    }
+
+    // ── put --merge-remote ───────────────────────────────────────────────────
+
+    #[test]
+    fn test_put_payload_replace_vs_merge_remote() {
+        use sm2env::diff::{diff_maps, KeyChange};
+
+        let remote = make_map(&[("DB_HOST", "old"), ("API_KEY", "k")]);
+        let local = make_map(&[("DB_HOST", "new"), ("DEBUG", "1")]);
+
+        let replaced = crate::put_payload(&remote, local.clone(), false);
+        assert_eq!(replaced, local);
+
+        let merged = crate::put_payload(&remote, local, true);
+        assert_eq!(Value::Object(merged.clone()), json!({"DB_HOST": "new", "API_KEY": "k", "DEBUG": "1"}));
+        let changes = diff_maps(&remote, &merged);
+        assert!(changes.iter().all(|(_, c)| !matches!(c, KeyChange::Removed { .. })));
+        assert_eq!(changes.len(), 2);
+
+        use clap::Parser;
+        let cli = crate::Cli::try_parse_from(["sm2env", "put", "app", "--file", ".env", "--merge-remote"]).unwrap();
+        assert!(matches!(cli.command, Some(crate::Commands::Put { merge_remote: true, .. })));
+    }
+
+    // ── Region precedence ────────────────────────────────────────────────────
+
+    #[test]
+    fn test_explicit_region_precedence() {
+        use sm2env::aws_client::{explicit_region, RegionSource};
+
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string())
+        };
+        let both = env(&[("AWS_REGION", "eu-west-1"), ("AWS_DEFAULT_REGION", "us-east-1")]);
+
+        assert_eq!(
+            explicit_region(Some("ap-south-1"), both),
+            Some(("ap-south-1".to_string(), RegionSource::Flag))
+        );
+        assert_eq!(
+            explicit_region(None, both),
+            Some(("eu-west-1".to_string(), RegionSource::EnvAwsRegion))
+        );
+        assert_eq!(
+            explicit_region(None, env(&[("AWS_REGION", ""), ("AWS_DEFAULT_REGION", "us-east-1")])),
+            Some(("us-east-1".to_string(), RegionSource::EnvAwsDefaultRegion))
+        );
+        assert_eq!(explicit_region(None, env(&[])), None);
+    }
+
+    // ── Watch ────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_current_version_finds_awscurrent() {
+        let versions: std::collections::HashMap<String, Vec<String>> = [
+            ("v1".to_string(), vec!["AWSPREVIOUS".to_string()]),
+            ("v2".to_string(), vec!["AWSPENDING".to_string(), "AWSCURRENT".to_string()]),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(crate::current_version(&versions), Some("v2"));
+        assert_eq!(crate::current_version(&Default::default()), None);
+    }
+
+    #[test]
+    fn test_watch_parses_interval() {
+        use clap::Parser;
+        let cli = crate::Cli::try_parse_from(["sm2env", "watch", "app", "--file", ".env", "--interval", "5"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(crate::Commands::Watch { interval: 5, output: None, .. })
+        ));
+        assert!(crate::Cli::try_parse_from(["sm2env", "watch", "app", "--interval", "0"]).is_err());
+    }
+
+    // ── YAML flow style ──────────────────────────────────────────────────────
+
+    #[test]
+    fn test_yaml_flow_style_is_inline() {
+        let data = json!({"a": 1, "b": {"c": "x"}, "list": ["p, q", true, null], "d": "yes"});
+        let map = data.as_object().unwrap();
+        assert_eq!(
+            converters::yaml::convert(map, false, true).unwrap(),
+            "{a: 1, b: {c: x}, list: [\"p, q\", true, null], d: \"yes\"}\n"
+        );
+        assert_eq!(converters::yaml::convert(map, true, true).unwrap().lines().next(), Some("---"));
+        // Flow output still parses back to the same data
+        let parsed: Value = serde_yml::from_str(&converters::yaml::convert(map, false, true).unwrap()).unwrap();
+        assert_eq!(parsed, data);
+    }
+
+    // ── Secret basename ──────────────────────────────────────────────────────
+
+    #[test]
+    fn test_secret_basename() {
+        use sm2env::aws_client::secret_basename;
+        assert_eq!(
+            secret_basename("arn:aws:secretsmanager:eu-west-1:123456789012:secret:prod/db-AbCdEf"),
+            "prod/db"
+        );
+        // Partial ARN without the random suffix
+        assert_eq!(secret_basename("arn:aws:secretsmanager:eu-west-1:123456789012:secret:app"), "app");
+        assert_eq!(secret_basename("prod/db-AbCdEf"), "prod/db-AbCdEf");
+        assert_eq!(secret_basename("my-app"), "my-app");
+        assert_eq!(
+            crate::output::sanitize_filename(secret_basename(
+                "arn:aws:secretsmanager:us-east-1:123456789012:secret:team/api-key-x1Y2z3"
+            )),
+            "team_api-key"
+        );
+    }
+
+    // ── Secure output dir ────────────────────────────────────────────────────
+
+    #[test]
+    #[cfg(unix)]
+    fn test_create_private_dir_is_0700() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("env/prod");
+        crate::output::create_private_dir(&nested).unwrap();
+        let mode = std::fs::metadata(&nested).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        // An existing, more open directory is tightened as well
+        let existing = dir.path().join("open");
+        std::fs::create_dir(&existing).unwrap();
+        std::fs::set_permissions(&existing, std::fs::Permissions::from_mode(0o755)).unwrap();
+        crate::output::create_private_dir(&existing).unwrap();
+        let mode = std::fs::metadata(&existing).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+    }
+
+    // ── List resume token ────────────────────────────────────────────────────
+
+    #[test]
+    fn test_list_start_token_parsing() {
+        use clap::Parser;
+        let cli = crate::Cli::try_parse_from(["sm2env", "list", "--start-token", "abc=="]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(crate::Commands::List { start_token: Some(ref t), .. }) if t == "abc=="
+        ));
+        assert!(crate::Cli::try_parse_from(["sm2env", "list", "--all-regions", "--start-token", "abc"]).is_err());
+    }
+
+    // ── SSM Parameter Store ──────────────────────────────────────────────────
+
+    #[test]
+    fn test_ssm_parameter_name() {
+        assert_eq!(crate::ssm_parameter_name("/myapp/", "DB_HOST"), "/myapp/DB_HOST");
+        assert_eq!(crate::ssm_parameter_name("/myapp", "DB_HOST"), "/myapp/DB_HOST");
+        assert_eq!(crate::ssm_parameter_name("/", "KEY"), "/KEY");
+    }
+
+    // ── Array values in env output ───────────────────────────────────────────
+
+    #[test]
+    fn test_env_joins_scalar_arrays() {
+        let data = json!({
+            "HOSTS": ["a", "b", "c"],
+            "MIXED": ["x", 1, true, 2.5],
+            "NESTED": [["a"], {"b": 1}],
+        });
+        let data = data.as_object().unwrap();
+        let env = converters::env::convert(data, &Default::default());
+        assert_eq!(env, "HOSTS=a,b,c\nMIXED=x,1,true,2.5\nNESTED=[[\"a\"],{\"b\":1}]\n");
+
+        let opts = converters::ConvertOptions { env_array_sep: Some(';'), ..Default::default() };
+        assert!(converters::env::convert(data, &opts).starts_with("HOSTS=a;b;c\n"));
+    }
+
+    // ── SM2ENV_OUTPUT default ────────────────────────────────────────────────
+
+    #[test]
+    fn test_output_default_reads_env_var() {
+        use clap::CommandFactory;
+        let cmd = crate::Cli::command();
+        for sub in ["get", "watch"] {
+            let output = cmd
+                .find_subcommand(sub)
+                .and_then(|c| c.get_arguments().find(|a| a.get_id() == "output"))
+                .unwrap();
+            assert_eq!(output.get_env(), Some(std::ffi::OsStr::new("SM2ENV_OUTPUT")));
+        }
+    }
+
+    // ── Keep comments ────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_env_keeps_comments_in_place() {
+        use crate::detect::{env_layout, EnvLine};
+
+        let text = "# Database\nDB_HOST=localhost\nDB_PORT=5432\n\n# Cache\nexport REDIS_URL=redis://x\nDB_HOST=db\n";
+        assert_eq!(
+            env_layout(text),
+            vec![
+                EnvLine::Verbatim("# Database".to_string()),
+                EnvLine::Key("DB_HOST".to_string()),
+                EnvLine::Key("DB_PORT".to_string()),
+                EnvLine::Verbatim(String::new()),
+                EnvLine::Verbatim("# Cache".to_string()),
+                EnvLine::Key("REDIS_URL".to_string()),
+                EnvLine::Key("DB_HOST".to_string()),
+            ]
+        );
+
+        let mut data = crate::detect::parse_env_vars(text);
+        data.insert("EXTRA".to_string(), json!("1"));
+        let env = converters::env::convert_with_layout(&data, &env_layout(text), &Default::default());
+        // A repeated key is written once, with its last value, at its first line
+        assert_eq!(
+            env,
+            "# Database\nDB_HOST=db\nDB_PORT=5432\n\n# Cache\nREDIS_URL=redis://x\nEXTRA=1\n"
+        );
+    }
+
+    // ── Max value length ─────────────────────────────────────────────────────
+
+    #[test]
+    fn test_max_value_length_rejects_oversized_values() {
+        let data = make_map(&[("SHORT", "abc"), ("BLOB", &"x".repeat(20))]);
+        assert_eq!(sm2env::oversized_values(&data, 10), vec![("BLOB", 20)]);
+        assert!(crate::check_value_lengths(&data, 20).is_ok());
+
+        let err = crate::check_value_lengths(&data, 10).unwrap_err();
+        assert!(matches!(err, sm2env::errors::SmError::FormatError(_)));
+        assert!(err.to_string().contains("BLOB (20 bytes)"), "{}", err);
+    }
+
+    // ── Compare version stages ───────────────────────────────────────────────
+
+    #[test]
+    fn test_compare_stages_flag() {
+        use clap::Parser;
+        let cli = crate::Cli::try_parse_from(["sm2env", "get", "app", "--compare-stages", "--reveal"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(crate::Commands::Get { compare_stages: true, reveal: true, .. })
+        ));
+        assert!(crate::Cli::try_parse_from(["sm2env", "get", "app", "--compare-stages", "--file", ".env"]).is_err());
+
+        // Previous on the left, current on the right
+        let previous = make_map(&[("USER", "app"), ("PASSWORD", "old"), ("LEGACY", "1")]);
+        let current = make_map(&[("USER", "app"), ("PASSWORD", "new")]);
+        let changes = sm2env::diff::diff_maps(&previous, &current);
+        assert_eq!(crate::diff_lines(&changes, false), vec!["~ PASSWORD", "- LEGACY"]);
+    }
+
+    // ── Format from --file extension ─────────────────────────────────────────
+
+    #[test]
+    fn test_output_format_from_file_extension() {
+        use crate::resolve_output_formats as resolve;
+
+        let inferred = |file: &str| resolve(&[], Some(file)).remove(0).to_string();
+        assert_eq!(inferred("out.json"), "json");
+        assert_eq!(inferred("config/out.yaml"), "yaml");
+        assert_eq!(inferred("out.YML"), "yaml");
+        assert_eq!(inferred("out.csv"), "csv");
+        assert_eq!(inferred("prod.auto.tfvars"), "tfvars");
+        assert_eq!(inferred("app.ini"), "ini");
+        assert_eq!(inferred("app.env"), "env");
+        assert_eq!(inferred(".env"), "env");
+        assert_eq!(inferred(".env.local"), "env");
+        assert_eq!(inferred(".envrc"), "direnv");
+        // Unknown extensions, stdout, and no file fall back to env
+        assert_eq!(inferred("out.toml"), "env");
+        assert_eq!(inferred("-"), "env");
+        assert_eq!(resolve(&[], None)[0].to_string(), "env");
+
+        // An explicit --output always wins
+        let explicit = resolve(&[OutputFormat::Yaml], Some("out.json"));
+        assert!(matches!(explicit.as_slice(), [OutputFormat::Yaml]));
+    }
+
+    // ── Logging ──────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_log_filter_precedence() {
+        use crate::ui::log_filter;
+        let rust_log = || Some("aws_config=debug".to_string());
+        assert_eq!(log_filter(Some("trace"), true, false, rust_log()), "sm2env=trace");
+        assert_eq!(log_filter(Some("error"), false, false, None), "sm2env=error");
+        assert_eq!(log_filter(None, true, false, rust_log()), "sm2env=debug");
+        assert_eq!(log_filter(None, false, false, rust_log()), "aws_config=debug");
+        assert_eq!(log_filter(None, false, false, Some(String::new())), "warn,sm2env=info");
+        assert_eq!(log_filter(None, false, false, None), "warn,sm2env=info");
+        assert_eq!(log_filter(None, false, true, None), "warn");
+    }
+
+    // ── Key case ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_key_case_modes() {
+        use converters::{ConvertOptions, KeyCase};
+
+        let data = make_map(&[("dbHost", "h"), ("API_key", "k"), ("lower", "l")]);
+        let env = |key_case| converters::env::convert(&data, &ConvertOptions { key_case, ..Default::default() });
+        assert_eq!(env(KeyCase::Preserve), "dbHost=h\nAPI_key=k\nlower=l\n");
+        assert_eq!(env(KeyCase::Upper), "DBHOST=h\nAPI_KEY=k\nLOWER=l\n");
+        assert_eq!(env(KeyCase::Lower), "dbhost=h\napi_key=k\nlower=l\n");
+
+        let opts = ConvertOptions { key_case: KeyCase::Upper, ..Default::default() };
+        assert!(converters::direnv::convert(&data, &opts).contains("export DBHOST=h\n"));
+        assert_eq!(converters::keys::convert(&data, &opts), "DBHOST\nAPI_KEY\nLOWER\n");
+        // Only ASCII letters change
+        assert_eq!(KeyCase::Upper.apply("straße_1"), "STRAßE_1");
+    }
+
+    #[test]
+    fn test_key_case_collisions_keep_the_first_key() {
+        use converters::{ConvertOptions, KeyCase};
+        let data = make_map(&[("db", "a"), ("DB", "b"), ("Db", "c"), ("other", "o")]);
+        assert_eq!(KeyCase::Upper.collisions(&data), vec![("DB", "db"), ("Db", "db")]);
+        assert!(KeyCase::Preserve.collisions(&data).is_empty());
+
+        let opts = ConvertOptions { key_case: KeyCase::Upper, ..Default::default() };
+        assert_eq!(converters::env::convert(&data, &opts), "DB=a\nOTHER=o\n");
+        assert_eq!(converters::keys::convert(&data, &opts), "DB\nOTHER\n");
+        assert_eq!(converters::direnv::convert(&data, &opts).lines().filter(|l| l.contains("DB=")).count(), 1);
+
+        let layout = sm2env::detect::env_layout("# db\ndb=a\nDB=b\nDb=c\nother=o\n");
+        assert_eq!(
+            converters::env::convert_with_layout(&data, &layout, &opts),
+            "# db\nDB=a\nOTHER=o\n"
+        );
+    }
+
+    // ── Pager ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_pager_command() {
+        use crate::ui::pager_command;
+        assert_eq!(pager_command(None), Some(vec!["less".to_string()]));
+        assert_eq!(
+            pager_command(Some("less -S".into())),
+            Some(vec!["less".to_string(), "-S".to_string()])
+        );
+        assert_eq!(pager_command(Some("".into())), None);
+        assert_eq!(pager_command(Some("cat".into())), None);
+    }
+
+    #[test]
+    fn test_no_pager_overrides_paginate() {
+        use clap::Parser;
+        let paging = |args: &[&str]| match crate::Cli::try_parse_from(args).unwrap().command {
+            Some(crate::Commands::List { paginate, no_pager, .. }) => paginate && !no_pager,
+            _ => unreachable!(),
+        };
+        assert!(paging(&["sm2env", "list", "--paginate"]));
+        assert!(!paging(&["sm2env", "list", "--paginate", "--no-pager"]));
+        assert!(paging(&["sm2env", "list", "--no-pager", "--paginate"]));
+        assert!(crate::Cli::try_parse_from(["sm2env", "list", "--paginate", "--count"]).is_err());
+    }
+
+    // ── list --ignore-case ────────────────────────────────────────────────
+
+    #[test]
+    fn test_list_ignore_case_is_accepted() {
+        use clap::Parser;
+        assert!(crate::Cli::try_parse_from(["sm2env", "list", "--filter", "prod", "--ignore-case"]).is_ok());
+        assert!(crate::Cli::try_parse_from([
+            "sm2env", "list", "--filter", "prod", "--ignore-case", "--case-sensitive"
+        ])
+        .is_err());
+    }
 }
@@ -0,0 +1,165 @@
+use owo_colors::OwoColorize;
+use std::ffi::OsString;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+static COLOR: AtomicBool = AtomicBool::new(true);
+
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Print an informational message to stdout unless `--quiet` is set.
+/// Secret data and errors must not go through this macro.
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if !$crate::ui::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+pub(crate) use info;
+
+/// Send `tracing` diagnostics to stderr, so stdout output is unaffected.
+/// `filter` is an `EnvFilter` directive such as `sm2env=debug`; see [`log_filter`].
+pub fn init_logging(filter: &str) {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::new(filter))
+        .with_writer(std::io::stderr)
+        .with_ansi(stderr_color())
+        .with_target(false)
+        .without_time()
+        .init();
+}
+
+/// Pick the log filter: `--log-level` (or `debug` for `--verbose`) applies to
+/// sm2env's own diagnostics; otherwise `RUST_LOG` is used as-is, so it can also
+/// enable the AWS SDK's logs. By default sm2env's warnings and reports (such as
+/// `--report-keys`) are shown, or only warnings with `--quiet`.
+pub fn log_filter(log_level: Option<&str>, verbose: bool, quiet: bool, rust_log: Option<String>) -> String {
+    match (log_level, verbose, rust_log) {
+        (Some(level), _, _) => format!("sm2env={}", level),
+        (None, true, _) => "sm2env=debug".to_string(),
+        (None, false, Some(env)) if !env.is_empty() => env,
+        _ if quiet => "warn".to_string(),
+        _ => "warn,sm2env=info".to_string(),
+    }
+}
+
+/// Decide whether color is allowed from `--no-color` and the `NO_COLOR` env var.
+pub fn init_color(no_color_flag: bool) {
+    COLOR.store(
+        color_allowed(no_color_flag, std::env::var_os("NO_COLOR")),
+        Ordering::Relaxed,
+    );
+}
+
+/// Color is off with `--no-color` or when `NO_COLOR` is set to a non-empty value.
+pub fn color_allowed(no_color_flag: bool, no_color_env: Option<OsString>) -> bool {
+    !no_color_flag && no_color_env.is_none_or(|v| v.is_empty())
+}
+
+fn stdout_color() -> bool {
+    COLOR.load(Ordering::Relaxed) && std::io::stdout().is_terminal()
+}
+
+fn stderr_color() -> bool {
+    COLOR.load(Ordering::Relaxed) && std::io::stderr().is_terminal()
+}
+
+/// Highlight a secret name printed to stdout.
+pub fn name(s: &str) -> String {
+    if stdout_color() {
+        s.cyan().to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+/// Emphasize a count printed to stdout.
+pub fn count(n: usize) -> String {
+    if stdout_color() {
+        n.bold().to_string()
+    } else {
+        n.to_string()
+    }
+}
+
+/// Color a `diff` line printed to stdout: green for `+`, red for `-`, yellow for `~`.
+pub fn diff_line(line: &str) -> String {
+    if !stdout_color() {
+        return line.to_string();
+    }
+    match line.chars().next() {
+        Some('+') => line.green().to_string(),
+        Some('-') => line.red().to_string(),
+        Some('~') => line.yellow().to_string(),
+        _ => line.to_string(),
+    }
+}
+
+/// Print `content` to stdout, through the user's pager when `paginate` is set and
+/// stdout is a terminal. Falls back to printing directly when no pager is
+/// configured or it cannot be started.
+pub fn print_paged(content: &str, paginate: bool) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let command = if paginate && std::io::stdout().is_terminal() {
+        pager_command(std::env::var_os("PAGER"))
+    } else {
+        None
+    };
+    let Some((program, args)) = command.as_ref().and_then(|c| c.split_first()) else {
+        print!("{}", content);
+        return Ok(());
+    };
+    let mut cmd = std::process::Command::new(program);
+    cmd.args(args).stdin(std::process::Stdio::piped());
+    if std::env::var_os("LESS").is_none() {
+        // Like git: quit if it fits on one screen, keep colors, don't clear the screen
+        cmd.env("LESS", "FRX");
+    }
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            tracing::debug!("could not start pager '{}': {}", program, e);
+            print!("{}", content);
+            return Ok(());
+        }
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        match stdin.write_all(content.as_bytes()) {
+            // The user quit the pager before reading everything
+            Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {}
+            result => result?,
+        }
+    }
+    child.wait()?;
+    Ok(())
+}
+
+/// The pager to run, split into program and arguments: `$PAGER`, or `less`
+/// when it is unset. `None` when `$PAGER` is empty or `cat`, meaning no pager.
+pub fn pager_command(pager_env: Option<OsString>) -> Option<Vec<String>> {
+    let pager = pager_env.map_or_else(|| "less".to_string(), |p| p.to_string_lossy().into_owned());
+    let words: Vec<String> = pager.split_whitespace().map(str::to_string).collect();
+    match words.first().map(String::as_str) {
+        None | Some("cat") => None,
+        Some(_) => Some(words),
+    }
+}
+
+/// Print an error to stderr with a red `Error:` label.
+pub fn print_error(message: impl std::fmt::Display) {
+    if stderr_color() {
+        eprintln!("{} {}", "Error:".red().bold(), message);
+    } else {
+        eprintln!("Error: {}", message);
+    }
+}